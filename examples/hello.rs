@@ -2,6 +2,7 @@ extern crate hublot;
 extern crate winit;
 
 use hublot::event_loop;
+use hublot::geom::Size;
 use hublot::{color, Color, UserInterface};
 use hublot::ui;
 
@@ -18,16 +19,17 @@ fn main() {
 
     let mut layout = ui::LinearLayout::new_vertical();
     layout.set_spacing(6f32);
-    let lbl1 = ui::Label::new(From::from(color::CssName::Chocolate));
-    let lbl2 = ui::Label::new(From::from(color::CssName::Coral));
+    let mut lbl1 = ui::Label::new(From::from(color::CssName::Chocolate));
+    lbl1.set_min_size(Size(120f32, 40f32));
+    let mut lbl2 = ui::Label::new(From::from(color::CssName::Coral));
+    lbl2.set_min_size(Size(80f32, 40f32));
 
-    let layout = ui::Node::new(layout, ui.clone(), None);
-    let lbl1 = ui::Node::new(lbl1, ui.clone(), None);
-    let lbl2 = ui::Node::new(lbl2, ui.clone(), None);
-    layout.add_child(&lbl1, None);
-    layout.add_child(&lbl2, None);
+    let root = ui::NodeBuilder::new(ui.clone(), layout)
+        .child(lbl1)
+        .child(lbl2)
+        .build();
 
-    ui.set_root(Some(layout));
+    ui.set_root(Some(root));
 
     event_loop::run(events_loop, vec![(window, ui)]);
 }
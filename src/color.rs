@@ -1,5 +1,7 @@
 use std::collections::HashMap;
+use std::error::Error;
 use std::fmt;
+use std::str::FromStr;
 
 /// Color represented with ARGB 8 bits per channel
 #[derive(Copy, Clone)]
@@ -24,39 +26,82 @@ impl Color {
     #[inline]
     pub fn new(r: u8, g: u8, b: u8, a: u8) -> Color {
         Color {
-            col: (r as u32).rotate_left(16)
-                | (g as u32).rotate_left(8)
-                | (b as u32)
-                | (a as u32).rotate_left(24),
+            col: (a as u32) << 24 | (r as u32) << 16 | (g as u32) << 8 | (b as u32),
         }
     }
+    /// Build a color from channels in [0, 1]. Channels are clamped to the valid
+    /// range and truncated to 8 bits.
     #[inline]
     pub fn fnew(r: f32, g: f32, b: f32, a: f32) -> Color {
-        Color::new(
-            (r * 255f32) as u8,
-            (g * 255f32) as u8,
-            (b * 255f32) as u8,
-            (a * 255f32) as u8,
-        )
+        let to_u8 = |v: f32| (clamp_unit(v) * 255f32) as u8;
+        Color::new(to_u8(r), to_u8(g), to_u8(b), to_u8(a))
     }
     pub fn from_name<S: AsRef<str>>(name: S) -> Option<Color> {
         CSS_NAMES.get(name.as_ref()).map(|&name| Color::from(name))
     }
+    /// Get the CSS name of the color, if it is exactly a named CSS color.
+    /// For colors with several names (e.g. "aqua" and "cyan"), the first in
+    /// alphabetical order is given.
+    pub fn name(&self) -> Option<&'static str> {
+        CSS_NAMES_REV.get(&self.col).cloned()
+    }
+    /// Get the CSS name of the named color closest to self, by Euclidean distance
+    /// of the red, green and blue channels. Alpha is ignored.
+    /// Ties are resolved by alphabetical order.
+    pub fn nearest_name(&self) -> &'static str {
+        let dist = |c: Color| {
+            let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+            d(self.red(), c.red()) + d(self.green(), c.green()) + d(self.blue(), c.blue())
+        };
+        CSS_NAMES_REV
+            .iter()
+            .map(|(&col, &name)| (dist(Color::from(col)), name))
+            .min()
+            .map(|(_, name)| name)
+            .unwrap()
+    }
+    /// Parse a color from "#rgb", "#rgba", "#rrggbb" or "#rrggbbaa" notation
+    pub fn from_hex<S: AsRef<str>>(hex: S) -> Option<Color> {
+        let hex = hex.as_ref();
+        if !hex.starts_with('#') || !hex.is_ascii() {
+            return None;
+        }
+        let hex = &hex[1..];
+        let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok();
+        let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        match hex.len() {
+            3 | 4 => {
+                let mut c = [0xffu8; 4];
+                for i in 0..hex.len() {
+                    c[i] = digit(i)? * 0x11;
+                }
+                Some(Color::from(c))
+            }
+            6 | 8 => {
+                let mut c = [0xffu8; 4];
+                for i in 0..hex.len() / 2 {
+                    c[i] = byte(2 * i)?;
+                }
+                Some(Color::from(c))
+            }
+            _ => None,
+        }
+    }
     #[inline]
     pub fn red(&self) -> u8 {
-        (self.col & 0x00ff0000).rotate_right(16) as u8
+        (self.col >> 16) as u8
     }
     #[inline]
     pub fn green(&self) -> u8 {
-        (self.col & 0x0000ff00).rotate_right(8) as u8
+        (self.col >> 8) as u8
     }
     #[inline]
     pub fn blue(&self) -> u8 {
-        (self.col & 0x000000ff) as u8
+        self.col as u8
     }
     #[inline]
     pub fn alpha(&self) -> u8 {
-        (self.col & 0xff000000).rotate_right(24) as u8
+        (self.col >> 24) as u8
     }
     #[inline]
     pub fn fred(&self) -> f32 {
@@ -74,6 +119,202 @@ impl Color {
     pub fn falpha(&self) -> f32 {
         self.alpha() as f32 / 255f32
     }
+    /// Linear interpolation between self (`t == 0`) and `other` (`t == 1`).
+    /// Channels are interpolated in straight alpha space. `t` is clamped to [0, 1].
+    pub fn lerp(&self, other: Color, t: f32) -> Color {
+        let t = t.max(0f32).min(1f32);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Color::new(
+            mix(self.red(), other.red()),
+            mix(self.green(), other.green()),
+            mix(self.blue(), other.blue()),
+            mix(self.alpha(), other.alpha()),
+        )
+    }
+    /// Linear interpolation in linear light between self (`t == 0`) and `other` (`t == 1`).
+    /// Midpoints are lighter than with `lerp`, which interpolates the sRGB encoded
+    /// channels, and fades between hues do not go through dark tones.
+    /// `t` is clamped to [0, 1].
+    pub fn lerp_linear(&self, other: Color, t: f32) -> Color {
+        let t = clamp_unit(t);
+        let (a, b) = (self.to_linear(), other.to_linear());
+        let mix = |i: usize| a[i] + (b[i] - a[i]) * t;
+        Color::from_linear([mix(0), mix(1), mix(2), mix(3)])
+    }
+    /// Get the same color with alpha replaced by `a`
+    #[inline]
+    pub fn with_alpha(&self, a: u8) -> Color {
+        Color {
+            col: (self.col & 0x00ffffff) | (a as u32) << 24,
+        }
+    }
+    /// Get the same color with alpha replaced by `a` in [0, 1].
+    /// `a` is clamped to the valid range.
+    #[inline]
+    pub fn with_falpha(&self, a: f32) -> Color {
+        self.with_alpha((a.max(0f32).min(1f32) * 255f32).round() as u8)
+    }
+    /// Get the same color with alpha multiplied by `factor`.
+    /// The resulting alpha is clamped to the valid range.
+    #[inline]
+    pub fn scale_alpha(&self, factor: f32) -> Color {
+        let a = (self.alpha() as f32 * factor).round().max(0f32).min(255f32);
+        self.with_alpha(a as u8)
+    }
+    /// Build a color from hue in degrees, saturation, lightness and alpha in [0, 1].
+    /// The hue wraps around 360 degrees.
+    pub fn from_hsl(h: f32, s: f32, l: f32, a: f32) -> Color {
+        let s = clamp_unit(s);
+        let l = clamp_unit(l);
+        let c = (1f32 - (2f32 * l - 1f32).abs()) * s;
+        let (r, g, b) = hue_to_rgb(h, c);
+        let m = l - c / 2f32;
+        Color::from_unit(r + m, g + m, b + m, a)
+    }
+    /// Convert to hue in degrees within [0, 360), saturation, lightness and alpha in [0, 1].
+    /// Achromatic colors (grays) have a hue and a saturation of 0.
+    pub fn to_hsl(&self) -> (f32, f32, f32, f32) {
+        let (h, max, min) = self.hue_max_min();
+        let l = (max + min) / 2f32;
+        let c = max - min;
+        let s = if c <= 0f32 {
+            0f32
+        } else {
+            c / (1f32 - (2f32 * l - 1f32).abs())
+        };
+        (h, s, l, self.falpha())
+    }
+    /// Build a color from hue in degrees, saturation, value and alpha in [0, 1].
+    /// The hue wraps around 360 degrees.
+    pub fn from_hsv(h: f32, s: f32, v: f32, a: f32) -> Color {
+        let s = clamp_unit(s);
+        let v = clamp_unit(v);
+        let c = v * s;
+        let (r, g, b) = hue_to_rgb(h, c);
+        let m = v - c;
+        Color::from_unit(r + m, g + m, b + m, a)
+    }
+    /// Convert to hue in degrees within [0, 360), saturation, value and alpha in [0, 1].
+    /// Achromatic colors (grays) have a hue and a saturation of 0.
+    pub fn to_hsv(&self) -> (f32, f32, f32, f32) {
+        let (h, max, min) = self.hue_max_min();
+        let s = if max <= 0f32 { 0f32 } else { (max - min) / max };
+        (h, s, max, self.falpha())
+    }
+    /// Hue in degrees and the largest and smallest of the red, green and blue channels
+    fn hue_max_min(&self) -> (f32, f32, f32) {
+        let (r, g, b) = (self.fred(), self.fgreen(), self.fblue());
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let c = max - min;
+        let h = if c <= 0f32 {
+            0f32
+        } else if max == r {
+            60f32 * ((g - b) / c)
+        } else if max == g {
+            60f32 * ((b - r) / c + 2f32)
+        } else {
+            60f32 * ((r - g) / c + 4f32)
+        };
+        let h = if h < 0f32 { h + 360f32 } else { h };
+        (h, max, min)
+    }
+    /// Build a color from channels in [0, 1], rounded to the nearest 8-bit value
+    fn from_unit(r: f32, g: f32, b: f32, a: f32) -> Color {
+        let to_u8 = |v: f32| (clamp_unit(v) * 255f32).round() as u8;
+        Color::new(to_u8(r), to_u8(g), to_u8(b), to_u8(a))
+    }
+    /// Get the channels in [0, 1], with red, green and blue converted from sRGB
+    /// encoding to linear light. Alpha is already linear and is left untouched.
+    pub fn to_linear(&self) -> [f32; 4] {
+        [
+            srgb_to_linear(self.fred()),
+            srgb_to_linear(self.fgreen()),
+            srgb_to_linear(self.fblue()),
+            self.falpha(),
+        ]
+    }
+    /// Build a color from linear light channels in [0, 1], encoded to sRGB and
+    /// rounded to the nearest 8-bit value. Alpha is stored untouched.
+    pub fn from_linear(c: [f32; 4]) -> Color {
+        Color::from_unit(
+            linear_to_srgb(clamp_unit(c[0])),
+            linear_to_srgb(clamp_unit(c[1])),
+            linear_to_srgb(clamp_unit(c[2])),
+            c[3],
+        )
+    }
+    /// Get the color with the red, green and blue channels multiplied by alpha
+    pub fn premultiplied(&self) -> Color {
+        let a = self.alpha() as u32;
+        let mul = |c: u8| ((c as u32 * a + 127) / 255) as u8;
+        Color::new(mul(self.red()), mul(self.green()), mul(self.blue()), self.alpha())
+    }
+    /// Get the straight alpha color from a premultiplied one.
+    /// A fully transparent color gives transparent black, as its channels are lost.
+    pub fn unpremultiplied(&self) -> Color {
+        let a = self.alpha() as u32;
+        if a == 0 {
+            return Color::new(0, 0, 0, 0);
+        }
+        let div = |c: u8| ((c as u32 * 255 + a / 2) / a).min(255) as u8;
+        Color::new(div(self.red()), div(self.green()), div(self.blue()), self.alpha())
+    }
+    /// Composite self over `dst` with the source-over operator.
+    /// Both colors and the result are in straight alpha.
+    pub fn over(&self, dst: Color) -> Color {
+        let sa = self.falpha();
+        let da = dst.falpha() * (1f32 - sa);
+        let a = sa + da;
+        if a <= 0f32 {
+            return Color::new(0, 0, 0, 0);
+        }
+        let blend = |s: f32, d: f32| ((s * sa + d * da) / a * 255f32).round() as u8;
+        Color::new(
+            blend(self.fred(), dst.fred()),
+            blend(self.fgreen(), dst.fgreen()),
+            blend(self.fblue(), dst.fblue()),
+            (a * 255f32).round() as u8,
+        )
+    }
+}
+
+/// Convert an sRGB encoded channel in [0, 1] to linear light
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045f32 {
+        c / 12.92f32
+    } else {
+        ((c + 0.055f32) / 1.055f32).powf(2.4f32)
+    }
+}
+
+/// Convert a linear light channel in [0, 1] to sRGB encoding
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308f32 {
+        c * 12.92f32
+    } else {
+        1.055f32 * c.powf(1f32 / 2.4f32) - 0.055f32
+    }
+}
+
+fn clamp_unit(v: f32) -> f32 {
+    v.max(0f32).min(1f32)
+}
+
+/// The red, green and blue channels of the given hue and chroma, before the
+/// lightness or value offset is added
+fn hue_to_rgb(h: f32, c: f32) -> (f32, f32, f32) {
+    let h = h % 360f32;
+    let h = if h < 0f32 { h + 360f32 } else { h } / 60f32;
+    let x = c * (1f32 - (h % 2f32 - 1f32).abs());
+    match h as u32 {
+        0 => (c, x, 0f32),
+        1 => (x, c, 0f32),
+        2 => (0f32, c, x),
+        3 => (0f32, x, c),
+        4 => (x, 0f32, c),
+        _ => (c, 0f32, x),
+    }
 }
 
 impl From<u32> for Color {
@@ -118,6 +359,27 @@ impl From<Color> for [f32; 4] {
     }
 }
 
+/// Error returned when a string is neither a hex color nor a CSS color name
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColorParseError(String);
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid color: \"{}\"", self.0)
+    }
+}
+
+impl Error for ColorParseError {}
+
+impl FromStr for Color {
+    type Err = ColorParseError;
+    fn from_str(s: &str) -> Result<Color, ColorParseError> {
+        Color::from_hex(s)
+            .or_else(|| Color::from_name(s))
+            .ok_or_else(|| ColorParseError(s.to_string()))
+    }
+}
+
 /// Standards: https://www.w3.org/TR/css3-color/#svg-color
 #[repr(u32)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -417,4 +679,157 @@ lazy_static! {
         m.shrink_to_fit();
         m
     };
+    /// Canonical name of each named color
+    static ref CSS_NAMES_REV: HashMap<u32, &'static str> = {
+        let mut m: HashMap<u32, &'static str> = HashMap::new();
+        for (&name, &css) in CSS_NAMES.iter() {
+            let entry = m.entry(css as u32).or_insert(name);
+            if name < *entry {
+                *entry = name;
+            }
+        }
+        m.shrink_to_fit();
+        m
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgba(c: Color) -> [u8; 4] {
+        c.into()
+    }
+
+    #[test]
+    fn from_hex() {
+        assert_eq!(rgba(Color::from_hex("#ff0000").unwrap()), [255, 0, 0, 255]);
+        assert_eq!(rgba(Color::from_hex("#12345678").unwrap()), [0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(rgba(Color::from_hex("#f80").unwrap()), [0xff, 0x88, 0x00, 0xff]);
+        assert_eq!(rgba(Color::from_hex("#1234").unwrap()), [0x11, 0x22, 0x33, 0x44]);
+        assert!(Color::from_hex("ff0000").is_none());
+        assert!(Color::from_hex("#12").is_none());
+        assert!(Color::from_hex("#gg0000").is_none());
+        assert!(Color::from_hex("#é00").is_none());
+    }
+
+    #[test]
+    fn from_str() {
+        let c: Color = "cadetblue".parse().unwrap();
+        assert_eq!(u32::from(c), CssName::CadetBlue as u32);
+        let c: Color = "#ff0000".parse().unwrap();
+        assert_eq!(rgba(c), [255, 0, 0, 255]);
+        assert_eq!(
+            "nope".parse::<Color>().unwrap_err(),
+            ColorParseError("nope".to_string())
+        );
+        assert!("#12".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn fnew_clamps() {
+        assert_eq!(rgba(Color::fnew(1.2f32, -0.1f32, 0.5f32, 2f32)), [255, 0, 127, 255]);
+        let c = Color::new(1, 2, 3, 4);
+        assert_eq!((c.red(), c.green(), c.blue(), c.alpha()), (1, 2, 3, 4));
+        assert_eq!(u32::from(c), 0x04010203);
+    }
+
+    #[test]
+    fn lerp() {
+        let black = Color::new(0, 0, 0, 255);
+        let white = Color::new(255, 255, 255, 255);
+        assert_eq!(rgba(black.lerp(white, 0f32)), rgba(black));
+        assert_eq!(rgba(black.lerp(white, 1f32)), rgba(white));
+        assert_eq!(rgba(black.lerp(white, 0.5f32)), [128, 128, 128, 255]);
+        assert_eq!(rgba(black.lerp(white, -1f32)), rgba(black));
+        assert_eq!(rgba(black.lerp(white, 2f32)), rgba(white));
+    }
+
+    #[test]
+    fn lerp_linear() {
+        let black = Color::new(0, 0, 0, 255);
+        let white = Color::new(255, 255, 255, 255);
+        assert_eq!(rgba(black.lerp_linear(white, 0f32)), rgba(black));
+        assert_eq!(rgba(black.lerp_linear(white, 1f32)), rgba(white));
+        let srgb = black.lerp(white, 0.5f32);
+        let linear = black.lerp_linear(white, 0.5f32);
+        assert!(linear.red() > srgb.red());
+        assert_eq!(linear.red(), 188);
+    }
+
+    #[test]
+    fn alpha() {
+        let c = Color::new(10, 20, 30, 128);
+        assert_eq!(rgba(c.with_alpha(7)), [10, 20, 30, 7]);
+        assert_eq!(rgba(c.with_falpha(1f32)), [10, 20, 30, 255]);
+        assert_eq!(rgba(c.with_falpha(-1f32)), [10, 20, 30, 0]);
+        assert_eq!(rgba(c.scale_alpha(0.5f32)), [10, 20, 30, 64]);
+        assert_eq!(rgba(c.scale_alpha(4f32)), [10, 20, 30, 255]);
+        assert_eq!(rgba(c.scale_alpha(-1f32)), [10, 20, 30, 0]);
+    }
+
+    #[test]
+    fn premultiply() {
+        let c = Color::new(200, 100, 50, 128);
+        assert_eq!(rgba(c.premultiplied()), [100, 50, 25, 128]);
+        assert_eq!(rgba(c.premultiplied().unpremultiplied()), [199, 100, 50, 128]);
+        assert_eq!(rgba(Color::new(3, 3, 3, 0).unpremultiplied()), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn over() {
+        let opaque = Color::new(10, 200, 30, 255);
+        assert_eq!(rgba(opaque.over(Color::new(1, 2, 3, 100))), rgba(opaque));
+        let x = Color::new(40, 50, 60, 170);
+        assert_eq!(rgba(Color::new(255, 255, 255, 0).over(x)), rgba(x));
+        let red = Color::new(255, 0, 0, 128);
+        let blue = Color::new(0, 0, 255, 255);
+        assert_eq!(rgba(red.over(blue)), [128, 0, 127, 255]);
+    }
+
+    #[test]
+    fn hsl_hsv_known() {
+        assert_eq!(Color::new(255, 0, 0, 255).to_hsl(), (0f32, 1f32, 0.5f32, 1f32));
+        assert_eq!(Color::new(0, 255, 0, 255).to_hsl(), (120f32, 1f32, 0.5f32, 1f32));
+        let (h, s, l, _) = Color::new(128, 128, 128, 255).to_hsl();
+        assert_eq!((h, s), (0f32, 0f32));
+        assert!((l - 0.502f32).abs() < 0.001f32);
+        assert_eq!(rgba(Color::from_hsl(240f32, 1f32, 0.5f32, 1f32)), [0, 0, 255, 255]);
+        assert_eq!(rgba(Color::from_hsl(-120f32, 1f32, 0.5f32, 1f32)), [0, 0, 255, 255]);
+        assert_eq!(rgba(Color::from_hsv(60f32, 1f32, 1f32, 1f32)), [255, 255, 0, 255]);
+        assert_eq!(Color::new(255, 255, 0, 255).to_hsv(), (60f32, 1f32, 1f32, 1f32));
+    }
+
+    #[test]
+    fn hsl_hsv_round_trip() {
+        let mut seed = 12345u32;
+        for _ in 0..1000 {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            let c = Color::from(seed | 0xff000000);
+            let (h, s, l, a) = c.to_hsl();
+            assert_eq!(rgba(Color::from_hsl(h, s, l, a)), rgba(c));
+            let (h, s, v, a) = c.to_hsv();
+            assert_eq!(rgba(Color::from_hsv(h, s, v, a)), rgba(c));
+        }
+    }
+
+    #[test]
+    fn linear() {
+        for v in 0..=255u8 {
+            let c = Color::new(v, v, v, v);
+            assert_eq!(rgba(Color::from_linear(c.to_linear())), rgba(c));
+        }
+        let l = Color::fnew(0.5f32, 0.5f32, 0.5f32, 1f32).to_linear();
+        assert!((l[0] - 0.214f32).abs() < 0.002f32);
+        assert_eq!(l[3], 1f32);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(Color::from(CssName::Red).name(), Some("red"));
+        assert_eq!(Color::from(CssName::Cyan).name(), Some("aqua"));
+        assert_eq!(Color::new(1, 2, 3, 4).name(), None);
+        assert_eq!(Color::new(250, 5, 3, 255).nearest_name(), "red");
+        assert_eq!(Color::from(CssName::Cyan).nearest_name(), "aqua");
+    }
 }
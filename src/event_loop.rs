@@ -1,54 +1,391 @@
 use crate::render;
+use crate::ui::anim::AnimationScheduler;
 use crate::{ui, UserInterface};
+use std::cell::RefCell;
 use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
 use winit;
 
-pub fn run(mut event_loop: winit::EventsLoop, mut windows: Vec<(winit::Window, Rc<UserInterface>)>) {
+/// Interval between two animation ticks, about 60 frames per second
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Builds a window and its user interface with the running events loop
+type WindowBuilder = Box<dyn FnOnce(&winit::EventsLoop) -> (winit::Window, Rc<UserInterface>)>;
+
+/// Handle to open windows while the event loop is running.
+/// Clones of the handle can be kept by the application, e.g. in a click callback.
+#[derive(Clone, Default)]
+pub struct Handle {
+    pending: Rc<RefCell<Vec<WindowBuilder>>>,
+    animations: AnimationScheduler,
+}
+
+impl Handle {
+    pub fn new() -> Handle {
+        Handle::default()
+    }
+
+    /// Request a window to be opened.
+    /// Windows can only be created with the events loop, which is borrowed while it
+    /// runs, so the window is not given directly. Instead `build` is called with the
+    /// events loop once the current event is handled. The window is then registered
+    /// to the render thread, which creates its surface and swapchain.
+    pub fn add_window<F>(&self, build: F)
+    where
+        F: FnOnce(&winit::EventsLoop) -> (winit::Window, Rc<UserInterface>) + 'static,
+    {
+        self.pending.borrow_mut().push(Box::new(build));
+    }
+
+    /// The scheduler ticked by the loop while it has animators.
+    /// Animators are called at each loop iteration with the time elapsed since the previous.
+    pub fn animations(&self) -> &AnimationScheduler {
+        &self.animations
+    }
+
+    fn has_pending(&self) -> bool {
+        !self.pending.borrow().is_empty()
+    }
+
+    fn take_pending(&self) -> Vec<WindowBuilder> {
+        self.pending.replace(Vec::new())
+    }
+}
+
+pub fn run(event_loop: winit::EventsLoop, windows: Vec<(winit::Window, Rc<UserInterface>)>) {
+    run_with(event_loop, windows, &Handle::new(), |_| {});
+}
+
+/// Same as `run`, but windows requested through `handle` are opened as the loop runs,
+/// and `on_window_closed` is called with the id of each window closed by the user,
+/// after it is removed from the loop.
+/// The loop stops when the last window is closed and no window is requested.
+pub fn run_with<F>(
+    mut event_loop: winit::EventsLoop,
+    mut windows: Vec<(winit::Window, Rc<UserInterface>)>,
+    handle: &Handle,
+    mut on_window_closed: F,
+) where
+    F: FnMut(winit::WindowId),
+{
     let wins: Vec<_> = windows.iter().map(|w| &(*w).0).collect();
     let render_thread = render::Thread::new(wins);
+    let animations = handle.animations();
+    let mut last_tick = Instant::now();
 
-    event_loop.run_forever(|event| {
-        let mut frames = Vec::new();
-
-        for w_ui in &windows {
-            if w_ui.1.dirty(ui::Dirty::STYLE) {
-                w_ui.1.style();
-            }
-            if w_ui.1.dirty(ui::Dirty::LAYOUT) {
-                w_ui.1.layout();
-            }
-            if w_ui.1.dirty(ui::Dirty::FRAME) {
-                frames.push(w_ui.1.frame(&w_ui.0));
+    loop {
+        if animations.is_active() {
+            // events are polled rather than waited for, so that animations keep running.
+            // ticks are paced to the frame interval to not spin while animating.
+            if let Some(delay) = frame_delay(last_tick, Instant::now()) {
+                thread::sleep(delay);
             }
+            let now = Instant::now();
+            animations.tick(now - last_tick);
+            last_tick = now;
+            event_loop.poll_events(|event| {
+                if let winit::Event::WindowEvent { window_id, event } = event {
+                    handle_event(
+                        window_id,
+                        event,
+                        &mut windows,
+                        &render_thread,
+                        &mut on_window_closed,
+                    );
+                }
+            });
+            emit_frames(&windows, &render_thread);
+        } else {
+            event_loop.run_forever(|event| {
+                // the event is handled first so that its effect is visible in the frames
+                // emitted in the same iteration
+                if let winit::Event::WindowEvent { window_id, event } = event {
+                    handle_event(
+                        window_id,
+                        event,
+                        &mut windows,
+                        &render_thread,
+                        &mut on_window_closed,
+                    );
+                }
+                emit_frames(&windows, &render_thread);
+
+                // leaving run_forever gives back access to the events loop
+                // for opening the requested windows and running animations
+                if windows.len() > 0 && !handle.has_pending() && !animations.is_active() {
+                    winit::ControlFlow::Continue
+                }
+                else {
+                    winit::ControlFlow::Break
+                }
+            });
+            // the time spent waiting for events is not part of the first animation tick
+            last_tick = Instant::now();
         }
 
-        if frames.len() > 0 {
-            render_thread.frames(frames);
+        let pending = handle.take_pending();
+        if windows.is_empty() && pending.is_empty() {
+            break;
+        }
+        for build in pending {
+            let (window, ui) = build(&event_loop);
+            add_window(window, ui, &mut windows, &render_thread);
         }
+    }
 
-        match event {
-            winit::Event::WindowEvent{window_id, event} => {
-                let idx = windows.iter().position(|w_ui| w_ui.0.id() == window_id);
-                if let Some(idx) = idx {
-                    let cf = windows[idx].1.handle_event(event);
-                    match cf {
-                        winit::ControlFlow::Break => {
-                            let _ = windows.remove(idx);
-                        }
-                        _ => {}
-                    }
-                }
+    render_thread.stop();
+}
+
+/// Time to wait at `now` before the animation tick following the one at `last_tick`
+fn frame_delay(last_tick: Instant, now: Instant) -> Option<Duration> {
+    let next_tick = last_tick + FRAME_INTERVAL;
+    if next_tick > now {
+        Some(next_tick - now)
+    } else {
+        None
+    }
+}
+
+/// The window operations performed by the loop.
+/// This lets the loop logic be tested without a display.
+trait LoopWindow {
+    type Id: Copy + PartialEq;
+
+    fn id(&self) -> Self::Id;
+
+    /// Build the frame of `ui`, shown in the window
+    fn frame(&self, ui: &UserInterface) -> render::Frame;
+}
+
+impl LoopWindow for winit::Window {
+    type Id = winit::WindowId;
+
+    fn id(&self) -> winit::WindowId {
+        winit::Window::id(self)
+    }
+
+    fn frame(&self, ui: &UserInterface) -> render::Frame {
+        ui.frame(self)
+    }
+}
+
+/// The render thread operations performed by the loop
+trait FrameSink<W: LoopWindow> {
+    fn add_window(&self, window: &W);
+    fn remove_window(&self, id: W::Id);
+    fn frames(&self, frames: Vec<render::Frame>);
+}
+
+impl FrameSink<winit::Window> for render::Thread {
+    fn add_window(&self, window: &winit::Window) {
+        render::Thread::add_window(self, window);
+    }
+
+    fn remove_window(&self, id: winit::WindowId) {
+        render::Thread::remove_window(self, id);
+    }
+
+    fn frames(&self, frames: Vec<render::Frame>) {
+        render::Thread::frames(self, frames);
+    }
+}
+
+/// Register a window opened while the loop runs to the render thread, and add it to
+/// the windows of the loop. Its frames are emitted from the next iteration.
+fn add_window<W, S>(
+    window: W,
+    ui: Rc<UserInterface>,
+    windows: &mut Vec<(W, Rc<UserInterface>)>,
+    render_thread: &S,
+) where
+    W: LoopWindow,
+    S: FrameSink<W>,
+{
+    render_thread.add_window(&window);
+    windows.push((window, ui));
+}
+
+/// Deliver an event to the user interface of the window `window_id`.
+/// A window whose user interface breaks the loop is closed.
+fn handle_event<W, S, F>(
+    window_id: W::Id,
+    event: winit::WindowEvent,
+    windows: &mut Vec<(W, Rc<UserInterface>)>,
+    render_thread: &S,
+    on_window_closed: &mut F,
+) where
+    W: LoopWindow,
+    S: FrameSink<W>,
+    F: FnMut(W::Id),
+{
+    let idx = windows.iter().position(|w_ui| w_ui.0.id() == window_id);
+    if let Some(idx) = idx {
+        let cf = windows[idx].1.handle_event(event);
+        match cf {
+            winit::ControlFlow::Break => {
+                let _ = windows.remove(idx);
+                render_thread.remove_window(window_id);
+                on_window_closed(window_id);
             }
-            _ => {},
+            _ => {}
         }
+    }
+}
+
+/// Send a frame to the render thread for each window whose user interface is dirty.
+/// A window asked by the system to be redrawn (WindowEvent::Refresh)
+/// has its FRAME flag set by handle_event.
+fn emit_frames<W, S>(windows: &[(W, Rc<UserInterface>)], render_thread: &S)
+where
+    W: LoopWindow,
+    S: FrameSink<W>,
+{
+    let mut frames = Vec::new();
 
-        if windows.len() > 0 {
-            winit::ControlFlow::Continue
+    for w_ui in windows {
+        if w_ui.1.dirty(ui::Dirty::STYLE) {
+            w_ui.1.style();
+        }
+        if w_ui.1.dirty(ui::Dirty::LAYOUT) {
+            w_ui.1.layout();
         }
-        else {
-            winit::ControlFlow::Break
+        if w_ui.1.dirty(ui::Dirty::FRAME) {
+            frames.push(w_ui.0.frame(&w_ui.1));
         }
-    });
+    }
 
-    render_thread.stop();
+    if frames.len() > 0 {
+        render_thread.frames(frames);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A window that logs the frames it builds
+    struct FakeWindow {
+        id: u32,
+        log: Rc<RefCell<Vec<u32>>>,
+    }
+
+    impl LoopWindow for FakeWindow {
+        type Id = u32;
+
+        fn id(&self) -> u32 {
+            self.id
+        }
+
+        fn frame(&self, ui: &UserInterface) -> render::Frame {
+            self.log.borrow_mut().push(self.id);
+            ui.frame_for(unsafe { winit::WindowId::dummy() }, (100, 100), 1f64)
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeSink {
+        added: RefCell<Vec<u32>>,
+        removed: RefCell<Vec<u32>>,
+        frames: RefCell<usize>,
+    }
+
+    impl FrameSink<FakeWindow> for FakeSink {
+        fn add_window(&self, window: &FakeWindow) {
+            self.added.borrow_mut().push(window.id);
+        }
+
+        fn remove_window(&self, id: u32) {
+            self.removed.borrow_mut().push(id);
+        }
+
+        fn frames(&self, frames: Vec<render::Frame>) {
+            *self.frames.borrow_mut() += frames.len();
+        }
+    }
+
+    fn windows(ids: &[u32], log: &Rc<RefCell<Vec<u32>>>) -> Vec<(FakeWindow, Rc<UserInterface>)> {
+        ids.iter()
+            .map(|&id| {
+                let win = FakeWindow {
+                    id,
+                    log: log.clone(),
+                };
+                (win, UserInterface::new())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn redraw_only_requested_window() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let sink = FakeSink::default();
+        let mut wins = windows(&[1, 2], &log);
+
+        emit_frames(&wins, &sink);
+        assert_eq!(*log.borrow(), vec![1, 2]);
+        assert!(wins.iter().all(|w| !w.1.dirty(ui::Dirty::FRAME)));
+
+        handle_event(2, winit::WindowEvent::Refresh, &mut wins, &sink, &mut |_| {});
+        assert!(!wins[0].1.dirty(ui::Dirty::FRAME));
+        assert!(wins[1].1.dirty(ui::Dirty::FRAME));
+
+        emit_frames(&wins, &sink);
+        assert_eq!(*log.borrow(), vec![1, 2, 2]);
+        assert_eq!(*sink.frames.borrow(), 3);
+
+        // nothing is dirty anymore
+        emit_frames(&wins, &sink);
+        assert_eq!(*sink.frames.borrow(), 3);
+    }
+
+    #[test]
+    fn close_requested() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let sink = FakeSink::default();
+        let mut wins = windows(&[1, 2], &log);
+        let mut closed = Vec::new();
+
+        handle_event(1, winit::WindowEvent::CloseRequested, &mut wins, &sink, &mut |id| {
+            closed.push(id)
+        });
+        assert_eq!(wins.len(), 1);
+        assert_eq!(wins[0].0.id, 2);
+        assert_eq!(*sink.removed.borrow(), vec![1]);
+        assert_eq!(closed, vec![1]);
+
+        // events to the closed window are dropped
+        handle_event(1, winit::WindowEvent::Refresh, &mut wins, &sink, &mut |_| {});
+        assert_eq!(wins.len(), 1);
+    }
+
+    #[test]
+    fn added_window_receives_frames() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let sink = FakeSink::default();
+        let mut wins = windows(&[1], &log);
+        emit_frames(&wins, &sink);
+        assert_eq!(*log.borrow(), vec![1]);
+
+        let (win, ui) = windows(&[2], &log).pop().unwrap();
+        add_window(win, ui, &mut wins, &sink);
+        assert_eq!(*sink.added.borrow(), vec![2]);
+
+        emit_frames(&wins, &sink);
+        assert_eq!(*log.borrow(), vec![1, 2]);
+
+        handle_event(2, winit::WindowEvent::Refresh, &mut wins, &sink, &mut |_| {});
+        emit_frames(&wins, &sink);
+        assert_eq!(*log.borrow(), vec![1, 2, 2]);
+        assert_eq!(*sink.frames.borrow(), 3);
+    }
+
+    #[test]
+    fn animation_ticks_are_paced() {
+        let t0 = Instant::now();
+        assert_eq!(frame_delay(t0, t0), Some(FRAME_INTERVAL));
+        assert_eq!(frame_delay(t0, t0 + Duration::from_millis(10)), Some(Duration::from_millis(6)));
+        assert_eq!(frame_delay(t0, t0 + FRAME_INTERVAL), None);
+        assert_eq!(frame_delay(t0, t0 + Duration::from_millis(40)), None);
+    }
 }
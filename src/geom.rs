@@ -1,4 +1,4 @@
-use std::ops::{Add, Index, IndexMut, Sub};
+use std::ops::{Add, Index, IndexMut, Mul, Sub};
 
 pub type FPoint = Point<f32>;
 pub type IPoint = Point<i32>;
@@ -23,7 +23,7 @@ impl<T: Copy> Point<T> {
         self.0
     }
     pub fn y(&self) -> T {
-        self.0
+        self.1
     }
 }
 
@@ -34,7 +34,7 @@ impl<T: Copy> Index<usize> for Point<T> {
         match idx {
             0 => &self.0,
             1 => &self.1,
-            _ => panic!(),
+            _ => panic!("index out of range: {} (expected 0 or 1)", idx),
         }
     }
 }
@@ -45,7 +45,7 @@ impl<T: Copy> IndexMut<usize> for Point<T> {
         match idx {
             0 => &mut self.0,
             1 => &mut self.1,
-            _ => panic!(),
+            _ => panic!("index out of range: {} (expected 0 or 1)", idx),
         }
     }
 }
@@ -69,6 +69,20 @@ impl From<winit::dpi::LogicalPosition> for FPoint {
     }
 }
 
+impl FPoint {
+    /// Convert logical coordinates into physical pixels, rounded to the nearest pixel
+    pub fn to_physical(self, scale: f64) -> IPoint {
+        Point(physical(self.0, scale), physical(self.1, scale))
+    }
+}
+
+impl IPoint {
+    /// Convert physical pixels into logical coordinates
+    pub fn to_logical(self, scale: f64) -> FPoint {
+        Point(logical(self.0, scale), logical(self.1, scale))
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct Vec<T>(pub T, pub T);
 
@@ -77,7 +91,7 @@ impl<T: Copy> Vec<T> {
         self.0
     }
     pub fn y(&self) -> T {
-        self.0
+        self.1
     }
 }
 
@@ -88,7 +102,7 @@ impl<T: Copy> Index<usize> for Vec<T> {
         match idx {
             0 => &self.0,
             1 => &self.1,
-            _ => panic!(),
+            _ => panic!("index out of range: {} (expected 0 or 1)", idx),
         }
     }
 }
@@ -99,7 +113,7 @@ impl<T: Copy> IndexMut<usize> for Vec<T> {
         match idx {
             0 => &mut self.0,
             1 => &mut self.1,
-            _ => panic!(),
+            _ => panic!("index out of range: {} (expected 0 or 1)", idx),
         }
     }
 }
@@ -124,6 +138,20 @@ impl<T: Add<Output = T>> Add for Vec<T> {
     }
 }
 
+impl<T: Sub<Output = T>> Sub for Vec<T> {
+    type Output = Vec<T>;
+    fn sub(self, other: Vec<T>) -> Vec<T> {
+        Vec(self.0 - other.0, self.1 - other.1)
+    }
+}
+
+impl<T: Copy + Mul<Output = T>> Mul<T> for Vec<T> {
+    type Output = Vec<T>;
+    fn mul(self, factor: T) -> Vec<T> {
+        Vec(self.0 * factor, self.1 * factor)
+    }
+}
+
 impl<T: Add<Output = T>> Add<Vec<T>> for Point<T> {
     type Output = Point<T>;
     fn add(self, other: Vec<T>) -> Point<T> {
@@ -131,6 +159,21 @@ impl<T: Add<Output = T>> Add<Vec<T>> for Point<T> {
     }
 }
 
+impl<T: Sub<Output = T>> Sub<Vec<T>> for Point<T> {
+    type Output = Point<T>;
+    fn sub(self, other: Vec<T>) -> Point<T> {
+        Point(self.0 - other.0, self.1 - other.1)
+    }
+}
+
+/// The vector going from `other` to `self`
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Vec<T>;
+    fn sub(self, other: Point<T>) -> Vec<T> {
+        Vec(self.0 - other.0, self.1 - other.1)
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct Size<T: Copy>(pub T, pub T);
 
@@ -155,6 +198,41 @@ impl From<ISize> for [i32; 2] {
     }
 }
 
+impl<T: Copy + Add<Output = T>> Add for Size<T> {
+    type Output = Size<T>;
+    fn add(self, other: Size<T>) -> Size<T> {
+        Size(self.0 + other.0, self.1 + other.1)
+    }
+}
+
+impl<T: Copy + Sub<Output = T>> Sub for Size<T> {
+    type Output = Size<T>;
+    fn sub(self, other: Size<T>) -> Size<T> {
+        Size(self.0 - other.0, self.1 - other.1)
+    }
+}
+
+impl<T: Copy + Mul<Output = T>> Mul<T> for Size<T> {
+    type Output = Size<T>;
+    fn mul(self, factor: T) -> Size<T> {
+        Size(self.0 * factor, self.1 * factor)
+    }
+}
+
+impl FSize {
+    /// Convert a logical size into physical pixels, rounded to the nearest pixel
+    pub fn to_physical(self, scale: f64) -> ISize {
+        Size(physical(self.0, scale), physical(self.1, scale))
+    }
+}
+
+impl ISize {
+    /// Convert a size in physical pixels into logical units
+    pub fn to_logical(self, scale: f64) -> FSize {
+        Size(logical(self.0, scale), logical(self.1, scale))
+    }
+}
+
 impl From<winit::dpi::LogicalSize> for FSize {
     fn from(size: winit::dpi::LogicalSize) -> Self {
         let (w, h): (f64, f64) = size.into();
@@ -226,8 +304,162 @@ where
     pub fn bottom(&self) -> T {
         self.y + self.height
     }
+    /// Get the rect translated by `v`
+    pub fn offset(&self, v: Vec<T>) -> Rect<T> {
+        Rect {
+            x: self.x + v.0,
+            y: self.y + v.1,
+            width: self.width,
+            height: self.height,
+        }
+    }
+    /// Translate the rect by `v`
+    pub fn offset_mut(&mut self, v: Vec<T>) {
+        self.x = self.x + v.0;
+        self.y = self.y + v.1;
+    }
+}
+
+impl<T> Rect<T>
+where
+    T: Copy,
+    T: Add<Output = T>,
+    T: Sub<Output = T>,
+    T: PartialOrd,
+    T: Default,
+{
+    /// An equivalent rect with non-negative width and height.
+    /// A negative extent is made positive by moving the origin to the opposite edge.
+    pub fn normalized(&self) -> Rect<T> {
+        let zero = T::default();
+        let (x, width) = if self.width < zero {
+            (self.x + self.width, zero - self.width)
+        } else {
+            (self.x, self.width)
+        };
+        let (y, height) = if self.height < zero {
+            (self.y + self.height, zero - self.height)
+        } else {
+            (self.y, self.height)
+        };
+        Rect::new(x, y, width, height)
+    }
+
+    /// Checks whether the rect has no area
+    pub fn is_empty(&self) -> bool {
+        let zero = T::default();
+        self.width == zero || self.height == zero
+    }
+
+    /// Checks whether the point is inside the rect.
+    /// The left and top edges are inside, the right and bottom edges are outside.
+    /// All the checks below work on the normalized rects.
+    pub fn contains_point(&self, p: Point<T>) -> bool {
+        let r = self.normalized();
+        p.0 >= r.x && p.0 < r.right() && p.1 >= r.y && p.1 < r.bottom()
+    }
+
+    /// Checks whether `other` lies entirely within the rect. Edges may be shared.
+    pub fn contains_rect(&self, other: Rect<T>) -> bool {
+        let (r, other) = (self.normalized(), other.normalized());
+        other.left() >= r.left()
+            && other.top() >= r.top()
+            && other.right() <= r.right()
+            && other.bottom() <= r.bottom()
+    }
+
+    /// Checks whether both rects overlap.
+    /// Rects that only share an edge do not overlap.
+    pub fn intersects(&self, other: Rect<T>) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// The area common to both rects, or `None` if they do not overlap.
+    /// Rects that only share an edge do not overlap.
+    pub fn intersection(&self, other: Rect<T>) -> Option<Rect<T>> {
+        let (r, other) = (self.normalized(), other.normalized());
+        let left = max(r.left(), other.left());
+        let top = max(r.top(), other.top());
+        let right = min(r.right(), other.right());
+        let bottom = min(r.bottom(), other.bottom());
+        if left < right && top < bottom {
+            Some(Rect::new(left, top, right - left, bottom - top))
+        } else {
+            None
+        }
+    }
+
+    /// The smallest rect containing both rects.
+    /// A rect without area is ignored, unless both rects are without area.
+    pub fn union(&self, other: Rect<T>) -> Rect<T> {
+        let (r, other) = (self.normalized(), other.normalized());
+        match (r.is_empty(), other.is_empty()) {
+            (false, true) => return r,
+            (true, false) => return other,
+            _ => {}
+        }
+        let left = min(r.left(), other.left());
+        let top = min(r.top(), other.top());
+        let right = max(r.right(), other.right());
+        let bottom = max(r.bottom(), other.bottom());
+        Rect::new(left, top, right - left, bottom - top)
+    }
+}
+
+impl FRect {
+    /// Convert a logical rect into physical pixels.
+    /// The edges are rounded to the nearest pixel, rather than the origin and size,
+    /// so that adjacent rects remain adjacent.
+    pub fn to_physical(self, scale: f64) -> IRect {
+        let left = physical(self.left(), scale);
+        let top = physical(self.top(), scale);
+        let right = physical(self.right(), scale);
+        let bottom = physical(self.bottom(), scale);
+        Rect::new(left, top, right - left, bottom - top)
+    }
+}
+
+impl IRect {
+    /// Convert a rect in physical pixels into logical units
+    pub fn to_logical(self, scale: f64) -> FRect {
+        Rect::new(
+            logical(self.x, scale),
+            logical(self.y, scale),
+            logical(self.width, scale),
+            logical(self.height, scale),
+        )
+    }
+}
+
+/// Logical to physical conversion. Halves are rounded away from zero, as `f64::round`.
+fn physical(logical: f32, scale: f64) -> i32 {
+    (logical as f64 * scale).round() as i32
+}
+
+fn logical(physical: i32, scale: f64) -> f32 {
+    (physical as f64 / scale) as f32
+}
+
+fn min<T: PartialOrd>(a: T, b: T) -> T {
+    if a < b {
+        a
+    } else {
+        b
+    }
 }
 
+fn max<T: PartialOrd>(a: T, b: T) -> T {
+    if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+/// Margins given in the order left, top, right, bottom.
+/// `rect + margins` grows the rect by the margins and `rect - margins` shrinks it.
+/// Negative margins are allowed and act in the opposite direction: adding negative
+/// margins shrinks the rect, which lets a view overlap its neighbours.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct Margins<T>(pub T, pub T, pub T, pub T);
 
@@ -252,6 +484,56 @@ impl<T: Copy + Add<Output = T>> Margins<T> {
     }
 }
 
+impl<T: Copy> Margins<T> {
+    /// Iterate over the margins in the order left, top, right, bottom
+    pub fn iter(&self) -> impl Iterator<Item = T> {
+        let margins = *self;
+        (0..4).map(move |idx| margins[idx])
+    }
+}
+
+impl<T: Copy> Index<usize> for Margins<T> {
+    type Output = T;
+    /// Index the margins in the order left, top, right, bottom
+    #[inline(always)]
+    fn index(&self, idx: usize) -> &Self::Output {
+        match idx {
+            0 => &self.0,
+            1 => &self.1,
+            2 => &self.2,
+            3 => &self.3,
+            _ => panic!("margins index out of range: {} (expected 0 to 3)", idx),
+        }
+    }
+}
+
+impl<T: Copy> IndexMut<usize> for Margins<T> {
+    #[inline(always)]
+    fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
+        match idx {
+            0 => &mut self.0,
+            1 => &mut self.1,
+            2 => &mut self.2,
+            3 => &mut self.3,
+            _ => panic!("margins index out of range: {} (expected 0 to 3)", idx),
+        }
+    }
+}
+
+impl<T: Copy + PartialOrd + Default> Margins<T> {
+    /// Whether any of the margins is negative
+    pub fn is_negative(&self) -> bool {
+        let zero = T::default();
+        self.0 < zero || self.1 < zero || self.2 < zero || self.3 < zero
+    }
+    /// Get the margins with negative values replaced by zero
+    pub fn clamp_non_negative(&self) -> Margins<T> {
+        let zero = T::default();
+        let clamp = |v: T| if v < zero { zero } else { v };
+        Margins(clamp(self.0), clamp(self.1), clamp(self.2), clamp(self.3))
+    }
+}
+
 impl From<FMargins> for [f32; 4] {
     fn from(val: FMargins) -> Self {
         [val.0, val.1, val.2, val.3]
@@ -283,7 +565,180 @@ impl<T: Copy + Add<Output = T> + Sub<Output = T>> Sub<Margins<T>> for Rect<T> {
             x: self.x + rhs.left(),
             y: self.y + rhs.top(),
             width: self.width - rhs.horizontal(),
-            height: self.height + rhs.vertical(),
+            height: self.height - rhs.vertical(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_vec_components() {
+        let p = Point(1, 2);
+        assert_eq!((p.x(), p.y()), (1, 2));
+        let v = Vec(3f32, 4f32);
+        assert_eq!((v.x(), v.y()), (3f32, 4f32));
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(Vec(1, 2) + Vec(3, 5), Vec(4, 7));
+        assert_eq!(Vec(1, 2) - Vec(3, 5), Vec(-2, -3));
+        assert_eq!(Vec(1, 2) * 3, Vec(3, 6));
+        assert_eq!(Point(1, 1) + Vec(2, 3), Point(3, 4));
+        assert_eq!(Point(1f32, 1f32) - Vec(1f32, 0f32), Point(0f32, 1f32));
+        assert_eq!(Point(5, 5) - Point(2, 1), Vec(3, 4));
+        assert_eq!(Size(1, 2) + Size(1, 1), Size(2, 3));
+        assert_eq!(Size(5, 4) - Size(2, 1), Size(3, 3));
+        assert_eq!(Size(1.5f32, 2f32) * 2f32, Size(3f32, 4f32));
+    }
+
+    #[test]
+    fn physical_logical() {
+        for &scale in &[1f64, 1.5f64, 2f64] {
+            let size = Size(100, 60);
+            assert_eq!(size.to_logical(scale).to_physical(scale), size);
+            let p = Point(-30, 45);
+            assert_eq!(p.to_logical(scale).to_physical(scale), p);
         }
+        assert_eq!(Size(3f32, 1f32).to_physical(1.5f64), Size(5, 2));
+        // halves are rounded away from zero
+        assert_eq!(Point(-0.5f32, 0.5f32).to_physical(1f64), Point(-1, 1));
+        // edges are rounded rather than the size
+        assert_eq!(
+            FRect::new(1f32, 1f32, 1f32, 1f32).to_physical(1.5f64),
+            IRect::new(2, 2, 1, 1)
+        );
+    }
+
+    #[test]
+    fn margins() {
+        let m = Margins(1, 2, 3, 4);
+        assert_eq!((m.horizontal(), m.vertical()), (4, 6));
+        let r = IRect::new(10, 10, 20, 20);
+        assert_eq!(r - m, IRect::new(11, 12, 16, 14));
+        assert_eq!(r + m, IRect::new(9, 8, 24, 26));
+        assert_eq!((r - m) + m, r);
+    }
+
+    #[test]
+    fn margins_negative() {
+        assert!(!Margins(0, 1, 2, 3).is_negative());
+        assert!(Margins(0, 1, -2, 3).is_negative());
+        assert!(Margins(-0.5f32, 0f32, 0f32, 0f32).is_negative());
+        assert_eq!(Margins(0, 1, 2, 3).clamp_non_negative(), Margins(0, 1, 2, 3));
+        assert_eq!(Margins(-1, 1, -2, 3).clamp_non_negative(), Margins(0, 1, 0, 3));
+    }
+
+    #[test]
+    fn margins_index() {
+        let mut m = Margins(1, 2, 3, 4);
+        assert_eq!((m[0], m[1], m[2], m[3]), (1, 2, 3, 4));
+        m[2] = 7;
+        assert_eq!(m.right(), 7);
+        let mut it = m.iter();
+        assert_eq!(it.next(), Some(1));
+        assert_eq!(it.nth(2), Some(4));
+        assert_eq!(it.next(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "margins index out of range: 4")]
+    fn margins_index_out_of_range() {
+        let m = Margins(1f32, 2f32, 3f32, 4f32);
+        let _ = m[4];
+    }
+
+    #[test]
+    fn normalized() {
+        assert_eq!(IRect::new(10, 10, -4, -6).normalized(), IRect::new(6, 4, 4, 6));
+        assert_eq!(
+            FRect::new(1f32, 1f32, -1f32, 2f32).normalized(),
+            FRect::new(0f32, 1f32, 1f32, 2f32)
+        );
+        let r = IRect::new(1, 2, 3, 4);
+        assert_eq!(r.normalized(), r);
+        assert!(IRect::new(10, 10, -4, -6).contains_point(Point(7, 5)));
+        assert!(IRect::new(1, 1, 0, 5).is_empty());
+        assert!(!IRect::new(1, 1, -1, 5).is_empty());
+    }
+
+    #[test]
+    fn offset() {
+        let r = IRect::new(1, 2, 3, 4);
+        assert_eq!(r.offset(Vec(10, -5)), IRect::new(11, -3, 3, 4));
+        let mut m = FRect::new(1f32, 2f32, 3f32, 4f32);
+        m.offset_mut(Vec(0.5f32, 1f32));
+        assert_eq!(m, FRect::new(1.5f32, 3f32, 3f32, 4f32));
+    }
+
+    #[test]
+    fn irect_relations() {
+        let a = IRect::new(0, 0, 10, 10);
+        let disjoint = IRect::new(20, 20, 5, 5);
+        let touching = IRect::new(10, 0, 5, 5);
+        let overlapping = IRect::new(8, 6, 5, 5);
+        let contained = IRect::new(2, 2, 8, 8);
+
+        assert!(a.contains_point(Point(0, 0)));
+        assert!(a.contains_point(Point(9, 9)));
+        assert!(!a.contains_point(Point(10, 5)));
+        assert!(!a.contains_point(Point(5, 10)));
+
+        assert!(!a.intersects(disjoint));
+        assert!(!a.intersects(touching));
+        assert!(a.intersects(overlapping));
+        assert!(a.intersects(contained));
+
+        assert_eq!(a.intersection(disjoint), None);
+        assert_eq!(a.intersection(touching), None);
+        assert_eq!(a.intersection(overlapping), Some(IRect::new(8, 6, 2, 4)));
+        assert_eq!(a.intersection(contained), Some(contained));
+
+        assert_eq!(a.union(disjoint), IRect::new(0, 0, 25, 25));
+        assert_eq!(a.union(touching), IRect::new(0, 0, 15, 10));
+        assert_eq!(a.union(overlapping), IRect::new(0, 0, 13, 11));
+        assert_eq!(a.union(contained), a);
+        assert_eq!(a.union(IRect::new(50, 50, 0, 0)), a);
+
+        assert!(a.contains_rect(contained));
+        assert!(a.contains_rect(a));
+        assert!(!a.contains_rect(overlapping));
+        assert!(!a.contains_rect(touching));
+    }
+
+    #[test]
+    fn frect_relations() {
+        let a = FRect::new(0f32, 0f32, 1f32, 1f32);
+        let disjoint = FRect::new(2f32, 2f32, 1f32, 1f32);
+        let touching = FRect::new(0f32, 1f32, 1f32, 1f32);
+        let overlapping = FRect::new(0.5f32, -1f32, 1f32, 1.5f32);
+        let contained = FRect::new(0.25f32, 0.25f32, 0.5f32, 0.5f32);
+
+        assert!(a.contains_point(Point(0.5f32, 0.999f32)));
+        assert!(!a.contains_point(Point(0.5f32, 1f32)));
+
+        assert!(!a.intersects(disjoint));
+        assert!(!a.intersects(touching));
+        assert!(a.intersects(overlapping));
+        assert!(a.intersects(contained));
+
+        assert_eq!(a.intersection(disjoint), None);
+        assert_eq!(a.intersection(touching), None);
+        assert_eq!(
+            a.intersection(overlapping),
+            Some(FRect::new(0.5f32, 0f32, 0.5f32, 0.5f32))
+        );
+        assert_eq!(a.intersection(contained), Some(contained));
+
+        assert_eq!(a.union(disjoint), FRect::new(0f32, 0f32, 3f32, 3f32));
+        assert_eq!(a.union(touching), FRect::new(0f32, 0f32, 1f32, 2f32));
+        assert_eq!(a.union(overlapping), FRect::new(0f32, -1f32, 1.5f32, 2f32));
+        assert_eq!(a.union(contained), a);
+
+        assert!(a.contains_rect(contained));
+        assert!(!a.contains_rect(overlapping));
     }
 }
@@ -1,3 +1,8 @@
+#[cfg(feature = "dx12")]
+extern crate gfx_backend_dx12 as gfx_back;
+#[cfg(feature = "metal")]
+extern crate gfx_backend_metal as gfx_back;
+#[cfg(feature = "vulkan")]
 extern crate gfx_backend_vulkan as gfx_back;
 extern crate gfx_hal as hal;
 extern crate winit;
@@ -9,6 +14,16 @@ extern crate downcast_rs;
 #[macro_use]
 extern crate lazy_static;
 
+#[cfg(not(any(feature = "vulkan", feature = "dx12", feature = "metal")))]
+compile_error!("a gfx backend must be enabled with one of the features: vulkan, dx12, metal");
+
+#[cfg(any(
+    all(feature = "vulkan", feature = "dx12"),
+    all(feature = "vulkan", feature = "metal"),
+    all(feature = "dx12", feature = "metal"),
+))]
+compile_error!("only one of the backend features vulkan, dx12 and metal can be enabled");
+
 pub mod color;
 pub mod event_loop;
 pub mod geom;
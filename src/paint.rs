@@ -1,13 +1,114 @@
 use crate::Color;
 
 pub mod gradient {
+    use crate::color::CssName;
+    use crate::geom::{FPoint, FSize, Point};
     use crate::Color;
 
     /// A control stop for color gradients
     pub struct Stop(pub f32, pub Color);
 
+    impl From<(f32, Color)> for Stop {
+        fn from(val: (f32, Color)) -> Stop {
+            Stop(val.0, val.1)
+        }
+    }
+
+    impl From<(f32, CssName)> for Stop {
+        fn from(val: (f32, CssName)) -> Stop {
+            Stop(val.0, Color::from(val.1))
+        }
+    }
+
+    /// Color of the gradient defined by `stops` at parameter `t`.
+    /// Stops are interpolated in sRGB space, and `t` is clamped to the end stops.
+    pub fn color_at(stops: &[Stop], t: f32) -> Color {
+        Interpolation::Srgb.color_at(stops, t)
+    }
+
+    /// Color space in which the stops of a gradient are interpolated
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub enum Interpolation {
+        /// Interpolate the sRGB encoded channels, as CSS gradients do
+        Srgb,
+        /// Interpolate in linear light, which avoids the darkened midpoints
+        /// of the sRGB interpolation
+        Linear,
+    }
+
+    impl Interpolation {
+        /// Color of the gradient defined by `stops` at parameter `t`,
+        /// interpolated in this color space. `t` is clamped to the end stops.
+        pub fn color_at(&self, stops: &[Stop], t: f32) -> Color {
+            match *self {
+                Interpolation::Srgb => interpolate(stops, t, Color::lerp),
+                Interpolation::Linear => interpolate(stops, t, Color::lerp_linear),
+            }
+        }
+    }
+
+    impl Default for Interpolation {
+        fn default() -> Interpolation {
+            Interpolation::Srgb
+        }
+    }
+
+    /// A gradient precomputed in a lookup table of evenly spaced colors.
+    /// The table length does not depend on the number of stops.
+    #[derive(Clone, Debug)]
+    pub struct Lut(Vec<Color>);
+
+    impl Lut {
+        /// Sample the gradient at `len` parameters evenly spaced in [0, 1],
+        /// interpolating the stops with `interpolation`
+        pub fn new(stops: &[Stop], len: usize, interpolation: Interpolation) -> Lut {
+            assert!(len >= 2, "a gradient lookup table needs at least 2 entries");
+            let colors = (0..len)
+                .map(|i| interpolation.color_at(stops, i as f32 / (len - 1) as f32))
+                .collect();
+            Lut(colors)
+        }
+
+        /// The colors of the table
+        pub fn colors(&self) -> &[Color] {
+            &self.0
+        }
+
+        /// Color of the nearest entry at parameter `t`, clamped to [0, 1]
+        pub fn sample(&self, t: f32) -> Color {
+            let last = self.0.len() - 1;
+            let i = (t.max(0f32).min(1f32) * last as f32).round() as usize;
+            self.0[i]
+        }
+    }
+
+    /// Interpolate the two stops around `t` with `lerp`
+    fn interpolate(stops: &[Stop], t: f32, lerp: fn(&Color, Color, f32) -> Color) -> Color {
+        match stops.len() {
+            0 => Color::new(0, 0, 0, 0),
+            1 => stops[0].1,
+            _ => {
+                if t <= stops[0].0 {
+                    return stops[0].1;
+                }
+                for pair in stops.windows(2) {
+                    let (a, b) = (&pair[0], &pair[1]);
+                    if t <= b.0 {
+                        let span = b.0 - a.0;
+                        let f = if span > 0f32 { (t - a.0) / span } else { 1f32 };
+                        return lerp(&a.1, b.1, f);
+                    }
+                }
+                stops[stops.len() - 1].1
+            }
+        }
+    }
+
     /// Direction of a linear gradient
     pub enum Direction {
+        /// Angle in radians of the gradient line, clockwise from the top (N) direction.
+        /// The line goes through the center and is as long as needed for the
+        /// corners to reach the first and last stops.
         Angle(f32),
         N,
         NE,
@@ -17,10 +118,344 @@ pub mod gradient {
         SW,
         W,
         NW,
+        /// Start and end points of the gradient line, as fractions of the rect size
+        Points(FPoint, FPoint),
+    }
+
+    impl Direction {
+        /// Start and end of the gradient line, in the coordinates of a rect of `size`
+        pub fn line(&self, size: FSize) -> (FPoint, FPoint) {
+            use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+            let (w, h) = (size.width(), size.height());
+            let angle = match *self {
+                Direction::Points(start, end) => {
+                    return (Point(start.0 * w, start.1 * h), Point(end.0 * w, end.1 * h));
+                }
+                Direction::Angle(angle) => angle,
+                Direction::N => 0f32,
+                Direction::NE => FRAC_PI_4,
+                Direction::E => FRAC_PI_2,
+                Direction::SE => FRAC_PI_2 + FRAC_PI_4,
+                Direction::S => PI,
+                Direction::SW => PI + FRAC_PI_4,
+                Direction::W => PI + FRAC_PI_2,
+                Direction::NW => PI + FRAC_PI_2 + FRAC_PI_4,
+            };
+            let (sin, cos) = angle.sin_cos();
+            // Y points downwards
+            let dir = (sin, -cos);
+            let half = (w * sin.abs() + h * cos.abs()) / 2f32;
+            let center = (w / 2f32, h / 2f32);
+            (
+                Point(center.0 - dir.0 * half, center.1 - dir.1 * half),
+                Point(center.0 + dir.0 * half, center.1 + dir.1 * half),
+            )
+        }
+
+        /// Gradient parameter at point `p` of a rect of `size`: the orthogonal projection
+        /// of `p` on the gradient line, 0 at the start and 1 at the end. It is not clamped.
+        /// A degenerate line of zero length gives 0 everywhere.
+        pub fn param(&self, size: FSize, p: FPoint) -> f32 {
+            let (start, end) = self.line(size);
+            let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+            let len2 = dx * dx + dy * dy;
+            if len2 <= std::f32::EPSILON {
+                return 0f32;
+            }
+            ((p.0 - start.0) * dx + (p.1 - start.1) * dy) / len2
+        }
+    }
+
+    /// Shape of a radial gradient
+    pub struct RadialSpec {
+        /// Center of the gradient, as a fraction of the rect size
+        pub center: FPoint,
+        /// Radius at which the last stop is reached, as a fraction of the distance
+        /// from the center to the farthest corner of the rect
+        pub radius: f32,
+    }
+
+    impl Default for RadialSpec {
+        fn default() -> RadialSpec {
+            RadialSpec {
+                center: Point(0.5f32, 0.5f32),
+                radius: 1f32,
+            }
+        }
+    }
+
+    /// Shape of a conic gradient, whose stops are laid out around the center
+    pub struct ConicSpec {
+        /// Center of the gradient, as a fraction of the rect size
+        pub center: FPoint,
+        /// Angle in radians of the first stop, clockwise from the positive X axis
+        pub start_angle: f32,
+    }
+
+    impl ConicSpec {
+        /// Gradient parameter in [0, 1) at point `p`, given as a fraction of the rect size.
+        /// The parameter grows clockwise from the start angle and wraps around it.
+        pub fn param(&self, p: FPoint) -> f32 {
+            use std::f32::consts::PI;
+            let angle = (p.1 - self.center.1).atan2(p.0 - self.center.0) - self.start_angle;
+            let t = angle / (2f32 * PI);
+            let t = t - t.floor();
+            // guard against t rounding to 1 for tiny negative angles
+            if t >= 1f32 {
+                0f32
+            } else {
+                t
+            }
+        }
+    }
+
+    impl Default for ConicSpec {
+        fn default() -> ConicSpec {
+            ConicSpec {
+                center: Point(0.5f32, 0.5f32),
+                start_angle: 0f32,
+            }
+        }
     }
 }
 
 pub enum Paint {
     Solid(Color),
     LinearGradient(Vec<gradient::Stop>, gradient::Direction),
+    RadialGradient(Vec<gradient::Stop>, gradient::RadialSpec),
+    ConicGradient(Vec<gradient::Stop>, gradient::ConicSpec),
+}
+
+impl Paint {
+    /// Multiply the alpha of every color of the paint by `factor`,
+    /// clamped as `Color::scale_alpha` does
+    pub fn scale_alpha(&mut self, factor: f32) {
+        match self {
+            Paint::Solid(color) => *color = color.scale_alpha(factor),
+            Paint::LinearGradient(stops, _)
+            | Paint::RadialGradient(stops, _)
+            | Paint::ConicGradient(stops, _) => {
+                for stop in stops.iter_mut() {
+                    stop.1 = stop.1.scale_alpha(factor);
+                }
+            }
+        }
+    }
+
+    /// Build a linear gradient paint from anything convertible to stops, such as
+    /// `(f32, Color)` or `(f32, CssName)` tuples
+    pub fn linear_gradient<I>(stops: I, direction: gradient::Direction) -> Paint
+    where
+        I: IntoIterator,
+        I::Item: Into<gradient::Stop>,
+    {
+        Paint::LinearGradient(stops.into_iter().map(Into::into).collect(), direction)
+    }
+
+    /// Build a radial gradient paint from anything convertible to stops
+    pub fn radial_gradient<I>(stops: I, spec: gradient::RadialSpec) -> Paint
+    where
+        I: IntoIterator,
+        I::Item: Into<gradient::Stop>,
+    {
+        Paint::RadialGradient(stops.into_iter().map(Into::into).collect(), spec)
+    }
+
+    /// Build a conic gradient paint from anything convertible to stops
+    pub fn conic_gradient<I>(stops: I, spec: gradient::ConicSpec) -> Paint
+    where
+        I: IntoIterator,
+        I::Item: Into<gradient::Stop>,
+    {
+        Paint::ConicGradient(stops.into_iter().map(Into::into).collect(), spec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::gradient::*;
+    use super::Paint;
+    use crate::color::CssName;
+    use crate::geom::{Point, Size};
+    use crate::Color;
+
+    fn black_white() -> Vec<Stop> {
+        vec![
+            Stop(0f32, Color::new(0, 0, 0, 255)),
+            Stop(1f32, Color::new(255, 255, 255, 255)),
+        ]
+    }
+
+    #[test]
+    fn lut_linear_midpoint() {
+        let stops = black_white();
+        let lut = Lut::new(&stops, 257, Interpolation::Linear);
+        let mid = lut.sample(0.5f32);
+        let srgb_mid = color_at(&stops, 0.5f32);
+        assert!(mid.red() > srgb_mid.red(), "{:?} <= {:?}", mid, srgb_mid);
+        assert_eq!(mid.red(), mid.green());
+        assert_eq!(mid.red(), mid.blue());
+    }
+
+    #[test]
+    fn lut_srgb_matches_color_at() {
+        let stops = black_white();
+        let lut = Lut::new(&stops, 5, Interpolation::Srgb);
+        for (i, color) in lut.colors().iter().enumerate() {
+            let t = i as f32 / 4f32;
+            assert_eq!(u32::from(*color), u32::from(color_at(&stops, t)), "entry {}", i);
+        }
+    }
+
+    #[test]
+    fn lut_sample_clamps_to_nearest() {
+        let stops = black_white();
+        let lut = Lut::new(&stops, 3, Interpolation::Srgb);
+        assert_eq!(lut.sample(-1f32).red(), 0);
+        assert_eq!(lut.sample(0.2f32).red(), 0);
+        assert_eq!(lut.sample(0.3f32).red(), lut.colors()[1].red());
+        assert_eq!(lut.sample(2f32).red(), 255);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 2 entries")]
+    fn lut_too_short() {
+        Lut::new(&black_white(), 1, Interpolation::Srgb);
+    }
+
+    fn alphas(paint: &Paint) -> Vec<u8> {
+        match paint {
+            Paint::Solid(color) => vec![color.alpha()],
+            Paint::LinearGradient(stops, _)
+            | Paint::RadialGradient(stops, _)
+            | Paint::ConicGradient(stops, _) => stops.iter().map(|s| s.1.alpha()).collect(),
+        }
+    }
+
+    #[test]
+    fn stop_conversions() {
+        let rgba = |stop: &Stop| (stop.0, <[u8; 4]>::from(stop.1));
+        let stop = Stop::from((0.25f32, Color::new(1, 2, 3, 4)));
+        assert_eq!(rgba(&stop), (0.25f32, [1, 2, 3, 4]));
+        let stop: Stop = (1f32, CssName::Red).into();
+        assert_eq!(rgba(&stop), (1f32, [255, 0, 0, 255]));
+
+        let paint = Paint::linear_gradient(
+            vec![(0f32, CssName::Red), (1f32, CssName::Blue)],
+            Direction::E,
+        );
+        match paint {
+            Paint::LinearGradient(stops, Direction::E) => {
+                let stops: Vec<_> = stops.iter().map(rgba).collect();
+                assert_eq!(stops, vec![(0f32, [255, 0, 0, 255]), (1f32, [0, 0, 255, 255])]);
+            }
+            _ => panic!("expected an eastward linear gradient"),
+        }
+    }
+
+    #[test]
+    fn paint_scale_alpha() {
+        let mut solid = Paint::Solid(Color::new(10, 20, 30, 128));
+        solid.scale_alpha(0.5f32);
+        assert_eq!(alphas(&solid), vec![64]);
+        // scaled up and clamped as colors are
+        solid.scale_alpha(3f32);
+        assert_eq!(alphas(&solid), vec![192]);
+        solid.scale_alpha(2f32);
+        assert_eq!(alphas(&solid), vec![255]);
+
+        let stops = vec![
+            Stop(0f32, Color::new(0, 0, 0, 100)),
+            Stop(1f32, Color::new(0, 0, 0, 200)),
+        ];
+        let mut gradient = Paint::LinearGradient(stops, Direction::E);
+        gradient.scale_alpha(1.5f32);
+        assert_eq!(alphas(&gradient), vec![150, 255]);
+        gradient.scale_alpha(-1f32);
+        assert_eq!(alphas(&gradient), vec![0, 0]);
+    }
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn direction_points_diagonal_equals_angle() {
+        use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+        let size = Size(10f32, 10f32);
+        let points = Direction::Points(Point(0f32, 0f32), Point(1f32, 1f32));
+        let angle = Direction::Angle(FRAC_PI_2 + FRAC_PI_4);
+        let (start, end) = points.line(size);
+        let (angle_start, angle_end) = angle.line(size);
+        assert!(approx(start.0, angle_start.0) && approx(start.1, angle_start.1));
+        assert!(approx(end.0, angle_end.0) && approx(end.1, angle_end.1));
+        for &p in &[Point(0f32, 0f32), Point(3f32, 8f32), Point(10f32, 10f32), Point(9f32, 2f32)] {
+            assert!(approx(points.param(size, p), angle.param(size, p)), "at {:?}", p);
+        }
+        assert!(approx(points.param(size, Point(5f32, 5f32)), 0.5f32));
+    }
+
+    #[test]
+    fn direction_zero_length_line() {
+        let dir = Direction::Points(Point(0.5f32, 0.5f32), Point(0.5f32, 0.5f32));
+        let size = Size(10f32, 20f32);
+        assert_eq!(dir.line(size), (Point(5f32, 10f32), Point(5f32, 10f32)));
+        assert_eq!(dir.param(size, Point(0f32, 0f32)), 0f32);
+        assert_eq!(dir.param(size, Point(5f32, 10f32)), 0f32);
+        // so is the line of an empty rect
+        assert_eq!(Direction::E.param(Size(0f32, 0f32), Point(1f32, 1f32)), 0f32);
+    }
+
+    #[test]
+    fn conic_param_clockwise_from_x() {
+        let spec = ConicSpec::default();
+        assert_eq!(spec.param(Point(1f32, 0.5f32)), 0f32);
+        // Y points downwards, so below the center is a quarter turn clockwise
+        assert!(approx(spec.param(Point(0.5f32, 1f32)), 0.25f32));
+        assert!(approx(spec.param(Point(0f32, 0.5f32)), 0.5f32));
+    }
+
+    #[test]
+    fn conic_param_wraps() {
+        let spec = ConicSpec::default();
+        // above the center is at a negative angle, wrapped to the last quarter
+        assert!(approx(spec.param(Point(0.5f32, 0f32)), 0.75f32));
+        let t = spec.param(Point(1f32, 0.49f32));
+        assert!(t > 0.99f32 && t < 1f32, "{}", t);
+    }
+
+    #[test]
+    fn conic_param_start_angle() {
+        use std::f32::consts::FRAC_PI_2;
+        let spec = ConicSpec {
+            start_angle: FRAC_PI_2,
+            ..ConicSpec::default()
+        };
+        assert!(approx(spec.param(Point(0.5f32, 1f32)), 0f32));
+        assert!(approx(spec.param(Point(0f32, 0.5f32)), 0.25f32));
+        assert!(approx(spec.param(Point(1f32, 0.5f32)), 0.75f32));
+    }
+
+    #[test]
+    fn conic_param_center() {
+        let spec = ConicSpec {
+            center: Point(0f32, 0f32),
+            ..ConicSpec::default()
+        };
+        assert!(approx(spec.param(Point(1f32, 1f32)), 0.125f32));
+        assert!(approx(spec.param(Point(0f32, 1f32)), 0.25f32));
+        assert_eq!(spec.param(Point(1f32, 0f32)), 0f32);
+    }
+
+    #[test]
+    fn conic_param_below_one() {
+        // a tiny negative angle wraps to a parameter that rounds to 1 in f32
+        let spec = ConicSpec {
+            start_angle: 1e-8f32,
+            ..ConicSpec::default()
+        };
+        let angle = -1e-8f32 / (2f32 * std::f32::consts::PI);
+        assert_eq!(angle - angle.floor(), 1f32);
+        assert_eq!(spec.param(Point(1f32, 0.5f32)), 0f32);
+    }
 }
@@ -0,0 +1,117 @@
+use crate::render::frame::{Node, NodeKind, WalkState};
+use std::collections::HashMap;
+
+/// Renderer of one kind of frame graph leaf nodes, recording its commands in `C`
+pub trait NodeRenderer<C> {
+    /// Record the commands drawing `node` with the state composed from its enclosing nodes.
+    /// The state model maps the node coordinates to physical pixels.
+    fn render(&mut self, cmd: &mut C, node: &Node, state: &WalkState);
+}
+
+/// Node renderers, keyed by the kind of leaf nodes they draw
+pub struct NodeRenderers<C> {
+    renderers: HashMap<NodeKind, Box<dyn NodeRenderer<C>>>,
+}
+
+impl<C> NodeRenderers<C> {
+    pub fn new() -> NodeRenderers<C> {
+        NodeRenderers {
+            renderers: HashMap::new(),
+        }
+    }
+
+    /// Register the renderer of the `kind` nodes and return the one it replaces, if any
+    pub fn register(
+        &mut self,
+        kind: NodeKind,
+        renderer: Box<dyn NodeRenderer<C>>,
+    ) -> Option<Box<dyn NodeRenderer<C>>> {
+        self.renderers.insert(kind, renderer)
+    }
+
+    /// Whether a renderer is registered for the `kind` nodes
+    pub fn has(&self, kind: NodeKind) -> bool {
+        self.renderers.contains_key(&kind)
+    }
+
+    /// Walk the graph from `root` and dispatch each leaf to the renderer of its kind.
+    /// Leaves without renderer are skipped.
+    pub fn render(&mut self, cmd: &mut C, root: &Node, state: &WalkState) {
+        let renderers = &mut self.renderers;
+        root.walk(state, &mut |node, state| {
+            let renderer = node.kind().and_then(|kind| renderers.get_mut(&kind));
+            if let Some(renderer) = renderer {
+                renderer.render(cmd, node, state);
+            }
+        });
+    }
+}
+
+impl<C> Default for NodeRenderers<C> {
+    fn default() -> NodeRenderers<C> {
+        NodeRenderers::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::{self, FRect, Point};
+    use crate::{Color, Paint, Transform};
+
+    /// Records the rect of each node it renders, mapped by the state model
+    struct RectRecorder;
+
+    impl NodeRenderer<Vec<FRect>> for RectRecorder {
+        fn render(&mut self, cmd: &mut Vec<FRect>, node: &Node, state: &WalkState) {
+            if let Node::Rect { rect, .. } = node {
+                let p = state.model * rect.point();
+                cmd.push(FRect::new_p(p, rect.width, rect.height));
+            }
+        }
+    }
+
+    fn rect_node(x: f32, y: f32) -> Node {
+        Node::Rect {
+            rect: FRect::new(x, y, 10f32, 10f32),
+            paint: Paint::Solid(Color::new(255, 0, 0, 255)),
+            radius: 0f32.into(),
+            border: None,
+        }
+    }
+
+    #[test]
+    fn rect_root_reaches_renderer() {
+        let mut renderers = NodeRenderers::new();
+        assert!(!renderers.has(NodeKind::Rect));
+        assert!(renderers.register(NodeKind::Rect, Box::new(RectRecorder)).is_none());
+        assert!(renderers.has(NodeKind::Rect));
+
+        let mut cmd = Vec::new();
+        renderers.render(&mut cmd, &rect_node(1f32, 2f32), &WalkState::default());
+        assert_eq!(cmd, vec![FRect::new(1f32, 2f32, 10f32, 10f32)]);
+    }
+
+    #[test]
+    fn leaves_dispatched_by_kind() {
+        let mut renderers = NodeRenderers::new();
+        renderers.register(NodeKind::Rect, Box::new(RectRecorder));
+
+        let translation = Transform::translation(geom::Vec(5f32, 0f32));
+        let root = Node::Group(vec![
+            rect_node(0f32, 0f32),
+            Node::Text {
+                rect: FRect::new(0f32, 0f32, 10f32, 10f32),
+                text: "skipped".to_string(),
+                font_size: 10f32,
+                color: Color::new(0, 0, 0, 255),
+            },
+            Node::transform(rect_node(0f32, 0f32), &translation),
+        ]);
+        let mut cmd = Vec::new();
+        renderers.render(&mut cmd, &root, &WalkState::default());
+        assert_eq!(cmd.len(), 2);
+        assert_eq!(cmd[0].point(), Point(0f32, 0f32));
+        assert_eq!(cmd[1].point(), Point(5f32, 0f32));
+    }
+}
@@ -1,10 +1,14 @@
-use crate::geom::{FRect, IRect};
-use crate::{Color, Paint};
+use crate::geom::{FRect, FVec, IRect, ISize, Point};
+use crate::{Color, Paint, Transform};
 use winit::WindowId;
 
+use std::sync::Arc;
+
 pub struct Frame {
     pub window: WindowId,
     pub viewport: IRect,
+    /// HiDPI factor of the window: ratio of physical pixels per logical unit
+    pub scale_factor: f32,
     pub clear_color: Option<Color>,
     pub root: Option<Node>,
 }
@@ -13,25 +17,385 @@ impl Frame {
     pub fn new(
         window: WindowId,
         viewport: IRect,
+        scale_factor: f32,
         clear_color: Option<Color>,
         root: Option<Node>,
     ) -> Frame {
         Frame {
             window,
             viewport,
+            scale_factor,
             clear_color,
             root,
         }
     }
+
+    /// Transform mapping the logical coordinates of the frame graph to the
+    /// physical pixels of the viewport. Renderers compose it with the model transform.
+    pub fn view_transform(&self) -> Transform {
+        Transform::homothetie([self.scale_factor, self.scale_factor])
+    }
+
+    /// Convert a logical length (e.g. a border width) into physical pixels
+    pub fn physical_length(&self, logical: f32) -> f32 {
+        logical * self.scale_factor
+    }
+}
+
+/// Radii of the corners of a rect, given in the order top-left, top-right,
+/// bottom-right, bottom-left. A zero radius gives a sharp corner.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CornerRadii(pub [f32; 4]);
+
+impl CornerRadii {
+    pub fn top_left(&self) -> f32 {
+        self.0[0]
+    }
+    pub fn top_right(&self) -> f32 {
+        self.0[1]
+    }
+    pub fn bottom_right(&self) -> f32 {
+        self.0[2]
+    }
+    pub fn bottom_left(&self) -> f32 {
+        self.0[3]
+    }
+    /// Whether all corners are sharp
+    pub fn is_sharp(&self) -> bool {
+        self.0.iter().all(|&r| r <= 0f32)
+    }
+    /// Whether all corners have the same radius
+    pub fn is_uniform(&self) -> bool {
+        self.0.iter().all(|&r| r == self.0[0])
+    }
+}
+
+impl From<f32> for CornerRadii {
+    fn from(radius: f32) -> CornerRadii {
+        CornerRadii([radius; 4])
+    }
+}
+
+impl From<[f32; 4]> for CornerRadii {
+    fn from(radii: [f32; 4]) -> CornerRadii {
+        CornerRadii(radii)
+    }
+}
+
+/// Stroke pattern of a border
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BorderStyle {
+    Solid,
+    /// Dashes of length `dash` separated by `gap`, both in logical units
+    Dashed { dash: f32, gap: f32 },
+    /// Square dots as long as the border width, separated by the border width
+    Dotted,
+}
+
+impl BorderStyle {
+    /// The (dash, gap) lengths of the pattern for a border of the given width,
+    /// or `None` for a solid border
+    pub fn dash_pattern(&self, width: f32) -> Option<(f32, f32)> {
+        match *self {
+            BorderStyle::Solid => None,
+            BorderStyle::Dashed { dash, gap } => Some((dash, gap)),
+            BorderStyle::Dotted => Some((width, width)),
+        }
+    }
+
+    /// Whether the border is drawn at the given arc length along the border.
+    /// This is the test a renderer performs to discard fragments within gaps.
+    pub fn is_drawn_at(&self, width: f32, arc_length: f32) -> bool {
+        match self.dash_pattern(width) {
+            None => true,
+            Some((dash, gap)) if dash + gap > 0f32 => {
+                let pos = arc_length % (dash + gap);
+                let pos = if pos < 0f32 { pos + dash + gap } else { pos };
+                pos < dash
+            }
+            Some(_) => false,
+        }
+    }
+}
+
+impl Default for BorderStyle {
+    fn default() -> BorderStyle {
+        BorderStyle::Solid
+    }
+}
+
+/// Border stroked inside the edges of a rect
+#[derive(Copy, Clone, Debug)]
+pub struct Border {
+    pub color: Color,
+    pub width: f32,
+    pub style: BorderStyle,
+}
+
+impl Border {
+    pub fn new(color: Color, width: f32, style: BorderStyle) -> Border {
+        Border {
+            color,
+            width,
+            style,
+        }
+    }
+}
+
+impl From<(Color, f32)> for Border {
+    fn from(val: (Color, f32)) -> Border {
+        Border::new(val.0, val.1, BorderStyle::Solid)
+    }
+}
+
+/// An image decoded in memory, with 8 bits per channel RGBA pixels in row order
+#[derive(Debug)]
+pub struct Image {
+    size: ISize,
+    data: Vec<u8>,
+}
+
+impl Image {
+    /// Build an image from its RGBA pixels.
+    /// Panics if `data` has not exactly 4 bytes per pixel of `size`.
+    pub fn new(size: ISize, data: Vec<u8>) -> Image {
+        assert!(size.width() >= 0 && size.height() >= 0);
+        assert_eq!(data.len(), 4 * (size.width() * size.height()) as usize);
+        Image { size, data }
+    }
+
+    /// The size in pixels
+    pub fn size(&self) -> ISize {
+        self.size
+    }
+
+    /// The RGBA pixels in row order
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
 }
 
 pub enum Node {
     Group(Vec<Node>),
     Transform(Box<Node>, [f32; 16]),
+    /// Restrict the rendering of the child subtree to the rect
+    Clip(FRect, Box<Node>),
+    /// Multiply the alpha of every color of the child subtree by a factor in [0, 1]
+    Opacity(f32, Box<Node>),
+    /// Child subtree drawn at the given z-index within the enclosing group.
+    /// Group children are drawn by increasing z-index, the other nodes having
+    /// a zero z-index, and in declaration order for equal z-indices.
+    Layer(i32, Box<Node>),
     Rect {
         rect: FRect,
         paint: Paint,
+        radius: CornerRadii,
+        border: Option<Border>,
+    },
+    /// Blurred rounded rect drawn behind content, typically grouped before the
+    /// rect that casts it. The shadow is `rect` moved by `offset` and spread over `blur`.
+    Shadow {
+        rect: FRect,
         radius: f32,
-        border: Option<(Color, f32)>,
+        blur: f32,
+        color: Color,
+        offset: FVec,
     },
+    /// Text drawn from the top-left corner of `rect` with a monospace font of
+    /// `font_size` (see `text::measure_ascii`)
+    Text {
+        rect: FRect,
+        text: String,
+        font_size: f32,
+        color: Color,
+    },
+    /// The `src` part of the image, in pixels, stretched over `rect`
+    Image {
+        rect: FRect,
+        image: Arc<Image>,
+        src: FRect,
+    },
+}
+
+/// Kind of the leaf nodes of a frame graph, to dispatch them to their renderer
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    Rect,
+    Shadow,
+    Text,
+    Image,
+}
+
+impl Node {
+    /// The kind of a leaf node, or `None` for group, transform, clip, opacity and layer nodes
+    pub fn kind(&self) -> Option<NodeKind> {
+        match self {
+            Node::Rect { .. } => Some(NodeKind::Rect),
+            Node::Shadow { .. } => Some(NodeKind::Shadow),
+            Node::Text { .. } => Some(NodeKind::Text),
+            Node::Image { .. } => Some(NodeKind::Image),
+            _ => None,
+        }
+    }
+
+    /// Wrap `child` in a transform node
+    pub fn transform(child: Node, transform: &Transform) -> Node {
+        Node::Transform(Box::new(child), transform.to_4x4_col_major())
+    }
+
+    /// Wrap self in an opacity node, unless opacity is 1 or more
+    pub fn with_opacity(self, opacity: f32) -> Node {
+        if opacity >= 1f32 {
+            self
+        } else {
+            Node::Opacity(opacity.max(0f32), Box::new(self))
+        }
+    }
+
+    /// The z-index of the node within its group
+    pub fn z_index(&self) -> i32 {
+        match self {
+            Node::Layer(z, _) => *z,
+            _ => 0,
+        }
+    }
+
+    /// Walk the graph depth-first in drawing order and call `f` on each leaf node
+    /// with the state composed from the enclosing nodes. Groups, transform, clip and
+    /// opacity and layer nodes are not passed to `f`.
+    pub fn walk<F>(&self, state: &WalkState, f: &mut F)
+    where
+        F: FnMut(&Node, &WalkState),
+    {
+        match self {
+            Node::Group(nodes) => {
+                if nodes.iter().any(|n| n.z_index() != 0) {
+                    let mut sorted: Vec<&Node> = nodes.iter().collect();
+                    // sort is stable: equal z-indices keep the declaration order
+                    sorted.sort_by_key(|n| n.z_index());
+                    for node in sorted {
+                        node.walk(state, f);
+                    }
+                } else {
+                    for node in nodes {
+                        node.walk(state, f);
+                    }
+                }
+            }
+            Node::Layer(_, child) => child.walk(state, f),
+            Node::Transform(child, mat) => {
+                let state = WalkState {
+                    model: state.model * Transform::from_4x4_col_major(mat),
+                    ..*state
+                };
+                child.walk(&state, f);
+            }
+            Node::Clip(rect, child) => {
+                let rect = bounding_rect(&state.model, rect);
+                let clip = match state.clip {
+                    Some(clip) => clip.intersection(rect),
+                    None => Some(rect),
+                };
+                // nothing of the subtree is visible if the clips do not overlap
+                if clip.is_some() {
+                    child.walk(&WalkState { clip, ..*state }, f);
+                }
+            }
+            Node::Opacity(opacity, child) => {
+                let opacity = state.opacity * opacity;
+                // a fully transparent subtree is not rendered
+                if opacity > 0f32 {
+                    child.walk(&WalkState { opacity, ..*state }, f);
+                }
+            }
+            _ => f(self, state),
+        }
+    }
+}
+
+/// State of the frame graph traversal, composed from the enclosing nodes
+#[derive(Copy, Clone, Debug)]
+pub struct WalkState {
+    /// Model transform, composed from the enclosing transform nodes
+    pub model: Transform,
+    /// Clip rect, after transformation by the model and intersection of the
+    /// enclosing clip nodes. A rotated clip is approximated by its bounding box.
+    pub clip: Option<FRect>,
+    /// Opacity, product of the enclosing opacity nodes
+    pub opacity: f32,
+}
+
+impl Default for WalkState {
+    fn default() -> WalkState {
+        WalkState {
+            model: Transform::identity(),
+            clip: None,
+            opacity: 1f32,
+        }
+    }
+}
+
+/// Bounding box of `rect` transformed by `model`
+pub(crate) fn bounding_rect(model: &Transform, rect: &FRect) -> FRect {
+    let corners = [
+        *model * Point(rect.left(), rect.top()),
+        *model * Point(rect.right(), rect.top()),
+        *model * Point(rect.right(), rect.bottom()),
+        *model * Point(rect.left(), rect.bottom()),
+    ];
+    let (mut l, mut t, mut r, mut b) = (corners[0].0, corners[0].1, corners[0].0, corners[0].1);
+    for c in &corners[1..] {
+        l = l.min(c.0);
+        t = t.min(c.1);
+        r = r.max(c.0);
+        b = b.max(c.1);
+    }
+    FRect::new(l, t, r - l, b - t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect_node(x: f32) -> Node {
+        Node::Rect {
+            rect: FRect::new(x, 0f32, 10f32, 10f32),
+            paint: Paint::Solid(Color::new(0, 0, 0, 255)),
+            radius: 0f32.into(),
+            border: None,
+        }
+    }
+
+    #[test]
+    fn view_transform_scales_to_pixels() {
+        let frame = Frame::new(
+            unsafe { WindowId::dummy() },
+            IRect::new(0, 0, 200, 200),
+            2f32,
+            None,
+            None,
+        );
+        let rect = FRect::new(0f32, 0f32, 100f32, 100f32);
+        let physical = bounding_rect(&frame.view_transform(), &rect);
+        assert_eq!(physical, FRect::new(0f32, 0f32, 200f32, 200f32));
+        assert_eq!(frame.physical_length(1.5f32), 3f32);
+    }
+
+    #[test]
+    fn walk_sorts_layers() {
+        // the x of each rect identifies it
+        let root = Node::Group(vec![
+            Node::Layer(2, Box::new(rect_node(0f32))),
+            rect_node(1f32),
+            Node::Layer(-1, Box::new(rect_node(2f32))),
+            Node::Layer(2, Box::new(rect_node(3f32))),
+        ]);
+        let mut xs = Vec::new();
+        root.walk(&WalkState::default(), &mut |node, _| {
+            if let Node::Rect { rect, .. } = node {
+                xs.push(rect.left());
+            }
+        });
+        assert_eq!(xs, vec![2f32, 1f32, 0f32, 3f32]);
+    }
 }
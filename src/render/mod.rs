@@ -6,9 +6,16 @@ use std::sync::{mpsc, Arc};
 use std::thread;
 use winit::{self, dpi::PhysicalSize, WindowId};
 
+pub mod dispatch;
 pub mod frame;
+#[cfg(feature = "soft")]
+pub mod soft;
+pub mod text;
 
+pub use self::dispatch::{NodeRenderer, NodeRenderers};
 pub use self::frame::Frame;
+#[cfg(feature = "soft")]
+pub use self::soft::SoftRenderer;
 
 pub struct Thread {
     instance: Arc<gfx::Instance>,
@@ -132,16 +139,15 @@ fn render_loop(instance: Arc<gfx::Instance>, windows: Vec<WindowInfo>, rx: mpsc:
     renderer.destroy();
 }
 
-trait NodeRenderer {
-
-}
-
 struct Renderer {
     physical_device: gfx::PhysicalDevice,
     device: gfx::Device,
     queues: gfx::QueueGroup,
     _memory_props: hal::MemoryProperties,
     windows: Vec<Window>,
+    /// Renderer of each kind of leaf nodes, created with the device and registered
+    /// in `Renderer::new`. Leaves of a kind without renderer are not drawn.
+    node_renderers: NodeRenderers<gfx::CommandBuffer>,
 }
 
 impl Renderer {
@@ -178,6 +184,7 @@ impl Renderer {
             queues,
             _memory_props: memory_props,
             windows: Vec::with_capacity(windows.len()),
+            node_renderers: NodeRenderers::new(),
         };
         renderer.windows = windows
             .into_iter()
@@ -245,6 +252,14 @@ impl Renderer {
                     );
                 }
 
+                if let Some(root) = frame.root.as_ref() {
+                    let state = frame::WalkState {
+                        model: frame.view_transform(),
+                        ..frame::WalkState::default()
+                    };
+                    self.node_renderers.render(cmd, root, &state);
+                }
+
                 cmd.finish();
 
                 let submission = hal::Submission {
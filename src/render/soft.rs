@@ -0,0 +1,693 @@
+use crate::geom::{FPoint, FRect, FSize, ISize, Margins, Point};
+use crate::paint::gradient::{Interpolation, Lut, Stop};
+use crate::render::frame::{bounding_rect, Border, CornerRadii, Frame, Image, Node, WalkState};
+use crate::render::text;
+use crate::{Color, Paint};
+
+/// Renders frames on the CPU in an RGBA buffer.
+/// This is meant for correctness tests on machines without GPU, not for performance.
+/// Pixels are sampled at their center without anti-aliasing.
+/// All leaf nodes are rendered: text with the bitmap font of `text::ascii_covers`,
+/// and shadows with a linear fade over their blur distance.
+#[derive(Debug)]
+pub struct SoftRenderer {
+    size: ISize,
+    pixels: Vec<u8>,
+    gradient_lut: Option<usize>,
+    gradient_interpolation: Interpolation,
+}
+
+impl SoftRenderer {
+    /// Create a renderer with a transparent buffer of `size` pixels
+    pub fn new(size: ISize) -> SoftRenderer {
+        assert!(size.width() >= 0 && size.height() >= 0);
+        SoftRenderer {
+            size,
+            pixels: vec![0u8; 4 * (size.width() * size.height()) as usize],
+            gradient_lut: None,
+            gradient_interpolation: Interpolation::default(),
+        }
+    }
+
+    /// The length of the lookup tables gradients are precomputed in, if any
+    pub fn gradient_lut(&self) -> Option<usize> {
+        self.gradient_lut
+    }
+
+    /// Precompute gradients in lookup tables of `len` colors (see `gradient::Lut`),
+    /// or interpolate the stops for each pixel if `None`
+    pub fn set_gradient_lut(&mut self, len: Option<usize>) {
+        self.gradient_lut = len;
+    }
+
+    /// The color space gradient stops are interpolated in
+    pub fn gradient_interpolation(&self) -> Interpolation {
+        self.gradient_interpolation
+    }
+
+    /// Interpolate gradient stops in `interpolation` color space, with or without
+    /// lookup tables. Stops are interpolated in sRGB space by default.
+    pub fn set_gradient_interpolation(&mut self, interpolation: Interpolation) {
+        self.gradient_interpolation = interpolation;
+    }
+
+    pub fn size(&self) -> ISize {
+        self.size
+    }
+
+    /// The RGBA pixels in row order
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Get the color of the pixel at `x`, `y`
+    pub fn pixel(&self, x: i32, y: i32) -> Color {
+        let i = self.index(x, y);
+        let p = &self.pixels[i..i + 4];
+        Color::new(p[0], p[1], p[2], p[3])
+    }
+
+    /// Fill the whole buffer with `color`
+    pub fn clear(&mut self, color: Color) {
+        for p in self.pixels.chunks_mut(4) {
+            p.copy_from_slice(&[color.red(), color.green(), color.blue(), color.alpha()]);
+        }
+    }
+
+    /// Render the frame. The buffer is cleared first if the frame has a clear color.
+    /// Frame coordinates are scaled by the frame scale factor.
+    pub fn render(&mut self, frame: &Frame) {
+        if let Some(color) = frame.clear_color {
+            self.clear(color);
+        }
+        if let Some(root) = frame.root.as_ref() {
+            let state = WalkState {
+                model: frame.view_transform(),
+                ..WalkState::default()
+            };
+            self.render_node(root, &state);
+        }
+    }
+
+    /// Render a frame graph node with the given initial state
+    pub fn render_node(&mut self, node: &Node, state: &WalkState) {
+        let lut_len = self.gradient_lut;
+        let interpolation = self.gradient_interpolation;
+        node.walk(state, &mut |node, state| match node {
+            Node::Rect {
+                rect,
+                paint,
+                radius,
+                border,
+            } => {
+                let lut = match (paint, lut_len) {
+                    (Paint::Solid(_), _) | (_, None) => None,
+                    (Paint::LinearGradient(stops, _), Some(len))
+                    | (Paint::RadialGradient(stops, _), Some(len))
+                    | (Paint::ConicGradient(stops, _), Some(len)) => {
+                        Some(Lut::new(stops, len, interpolation))
+                    }
+                };
+                self.fill_rect(state, *rect, |p| {
+                    if !in_rounded_rect(*rect, radius, p) {
+                        return None;
+                    }
+                    if let Some(border) = border {
+                        if in_border(*rect, radius, border, p) {
+                            return Some(border.color);
+                        }
+                    }
+                    Some(paint_color(paint, lut.as_ref(), interpolation, *rect, p))
+                })
+            }
+            Node::Image { rect, image, src } => {
+                self.fill_rect(state, *rect, |p| Some(sample(image, *rect, *src, p)))
+            }
+            Node::Shadow {
+                rect,
+                radius,
+                blur,
+                color,
+                offset,
+            } => {
+                let shadow = rect.offset(*offset);
+                let spread = blur / 2f32;
+                let bounds = shadow + Margins(spread, spread, spread, spread);
+                self.fill_rect(state, bounds, |p| {
+                    let alpha = shadow_coverage(shadow, *radius, *blur, p);
+                    if alpha > 0f32 {
+                        Some(color.scale_alpha(alpha))
+                    } else {
+                        None
+                    }
+                })
+            }
+            Node::Text {
+                rect,
+                text,
+                font_size,
+                color,
+            } => self.fill_rect(state, *rect, |p| {
+                let local = Point(p.0 - rect.x, p.1 - rect.y);
+                if text::ascii_covers(text, *font_size, local) {
+                    Some(*color)
+                } else {
+                    None
+                }
+            }),
+            _ => {}
+        });
+    }
+
+    /// Blend the color given by `color_at` over the pixels covered by `rect`.
+    /// `color_at` receives positions in the coordinates of `rect`, before transformation,
+    /// and returns `None` for positions that are not covered.
+    fn fill_rect<F>(&mut self, state: &WalkState, rect: FRect, color_at: F)
+    where
+        F: Fn(FPoint) -> Option<Color>,
+    {
+        let inv = match state.model.inverse() {
+            Some(inv) => inv,
+            None => return,
+        };
+        let target = FRect::new(
+            0f32,
+            0f32,
+            self.size.width() as f32,
+            self.size.height() as f32,
+        );
+        let bounds = bounding_rect(&state.model, &rect).intersection(target);
+        let bounds = match (bounds, state.clip) {
+            (Some(bounds), Some(clip)) => bounds.intersection(clip),
+            (bounds, None) => bounds,
+            (None, _) => None,
+        };
+        let bounds = match bounds {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let x0 = bounds.left().floor() as i32;
+        let y0 = bounds.top().floor() as i32;
+        let x1 = (bounds.right().ceil() as i32).min(self.size.width());
+        let y1 = (bounds.bottom().ceil() as i32).min(self.size.height());
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let center = Point(x as f32 + 0.5f32, y as f32 + 0.5f32);
+                if let Some(clip) = state.clip {
+                    if !clip.contains_point(center) {
+                        continue;
+                    }
+                }
+                let p = inv * center;
+                if !rect.contains_point(p) {
+                    continue;
+                }
+                if let Some(color) = color_at(p) {
+                    self.blend(x, y, color.scale_alpha(state.opacity));
+                }
+            }
+        }
+    }
+
+    fn blend(&mut self, x: i32, y: i32, color: Color) {
+        let color = color.over(self.pixel(x, y));
+        let i = self.index(x, y);
+        self.pixels[i..i + 4].copy_from_slice(&[
+            color.red(),
+            color.green(),
+            color.blue(),
+            color.alpha(),
+        ]);
+    }
+
+    fn index(&self, x: i32, y: i32) -> usize {
+        assert!(x >= 0 && x < self.size.width() && y >= 0 && y < self.size.height());
+        4 * (y * self.size.width() + x) as usize
+    }
+}
+
+/// Checks whether `p` is inside `rect` with rounded corners
+fn in_rounded_rect(rect: FRect, radius: &CornerRadii, p: FPoint) -> bool {
+    let corners = [
+        (radius.top_left(), Point(rect.left(), rect.top()), (1f32, 1f32)),
+        (radius.top_right(), Point(rect.right(), rect.top()), (-1f32, 1f32)),
+        (radius.bottom_right(), Point(rect.right(), rect.bottom()), (-1f32, -1f32)),
+        (radius.bottom_left(), Point(rect.left(), rect.bottom()), (1f32, -1f32)),
+    ];
+    corners.iter().all(|&(r, corner, dir)| {
+        if r <= 0f32 {
+            return true;
+        }
+        let center = Point(corner.0 + dir.0 * r, corner.1 + dir.1 * r);
+        let (dx, dy) = (p.0 - center.0, p.1 - center.1);
+        // only the quarter of the corner needs the distance check
+        let in_quarter = dx * dir.0 < 0f32 && dy * dir.1 < 0f32;
+        !in_quarter || dx * dx + dy * dy <= r * r
+    })
+}
+
+/// Coverage in [0, 1] of the shadow of the rounded rect `rect` at `p`.
+/// The coverage fades linearly over `blur`, centered on the edge of the rect.
+fn shadow_coverage(rect: FRect, radius: f32, blur: f32, p: FPoint) -> f32 {
+    // signed distance to the rounded rect, negative inside
+    let half = (rect.width / 2f32, rect.height / 2f32);
+    let radius = radius.max(0f32).min(half.0).min(half.1);
+    let q = (
+        (p.0 - rect.x - half.0).abs() - (half.0 - radius),
+        (p.1 - rect.y - half.1).abs() - (half.1 - radius),
+    );
+    let outside = (q.0.max(0f32), q.1.max(0f32));
+    let dist = (outside.0 * outside.0 + outside.1 * outside.1).sqrt() + q.0.max(q.1).min(0f32)
+        - radius;
+    if blur <= 0f32 {
+        if dist <= 0f32 {
+            1f32
+        } else {
+            0f32
+        }
+    } else {
+        (0.5f32 - dist / blur).max(0f32).min(1f32)
+    }
+}
+
+/// Checks whether `p`, inside the rounded rect, is on the border
+fn in_border(rect: FRect, radius: &CornerRadii, border: &Border, p: FPoint) -> bool {
+    let w = border.width;
+    if w <= 0f32 {
+        return false;
+    }
+    let inner = FRect::new(
+        rect.x + w,
+        rect.y + w,
+        rect.width - 2f32 * w,
+        rect.height - 2f32 * w,
+    );
+    let inner_radius = CornerRadii([
+        radius.top_left() - w,
+        radius.top_right() - w,
+        radius.bottom_right() - w,
+        radius.bottom_left() - w,
+    ]);
+    if inner.contains_point(p) && in_rounded_rect(inner, &inner_radius, p) {
+        return false;
+    }
+    // arc length along the border, clockwise from the top-left corner
+    let (lx, ly) = (p.0 - rect.x, p.1 - rect.y);
+    let (rw, rh) = (rect.width, rect.height);
+    let arc = if ly < w {
+        lx
+    } else if lx >= rw - w {
+        rw + ly
+    } else if ly >= rh - w {
+        rw + rh + (rw - lx)
+    } else {
+        2f32 * rw + rh + (rh - ly)
+    };
+    border.style.is_drawn_at(w, arc)
+}
+
+/// Color of the paint at `p` within `rect`. Gradients are sampled from `lut` if given,
+/// and their stops interpolated with `interpolation` otherwise.
+fn paint_color(
+    paint: &Paint,
+    lut: Option<&Lut>,
+    interpolation: Interpolation,
+    rect: FRect,
+    p: FPoint,
+) -> Color {
+    let stops_color = |stops: &[Stop], t: f32| match lut {
+        Some(lut) => lut.sample(t),
+        None => interpolation.color_at(stops, t),
+    };
+    let local = Point(p.0 - rect.x, p.1 - rect.y);
+    let size: FSize = rect.size();
+    match paint {
+        Paint::Solid(color) => *color,
+        Paint::LinearGradient(stops, direction) => {
+            stops_color(stops, direction.param(size, local))
+        }
+        Paint::RadialGradient(stops, spec) => {
+            let (w, h) = (size.width(), size.height());
+            let center = Point(spec.center.0 * w, spec.center.1 * h);
+            let far = |c: f32, extent: f32| c.max(extent - c);
+            let reach = {
+                let (fx, fy) = (far(center.0, w), far(center.1, h));
+                (fx * fx + fy * fy).sqrt() * spec.radius
+            };
+            let (dx, dy) = (local.0 - center.0, local.1 - center.1);
+            let t = if reach > 0f32 {
+                (dx * dx + dy * dy).sqrt() / reach
+            } else {
+                0f32
+            };
+            stops_color(stops, t)
+        }
+        Paint::ConicGradient(stops, spec) => {
+            let frac = Point(
+                local.0 / size.width().max(std::f32::EPSILON),
+                local.1 / size.height().max(std::f32::EPSILON),
+            );
+            stops_color(stops, spec.param(frac))
+        }
+    }
+}
+
+/// Nearest sample of the `src` part of `image` stretched over `rect`, at `p`
+fn sample(image: &Image, rect: FRect, src: FRect, p: FPoint) -> Color {
+    let size = image.size();
+    let u = (p.0 - rect.x) / rect.width;
+    let v = (p.1 - rect.y) / rect.height;
+    let x = (src.x + u * src.width).floor() as i32;
+    let y = (src.y + v * src.height).floor() as i32;
+    if x < 0 || y < 0 || x >= size.width() || y >= size.height() {
+        return Color::new(0, 0, 0, 0);
+    }
+    let i = 4 * (y * size.width() + x) as usize;
+    let d = &image.data()[i..i + 4];
+    Color::new(d[0], d[1], d[2], d[3])
+}
+
+#[cfg(all(test, feature = "soft"))]
+mod tests {
+    use super::*;
+    use crate::geom::{IRect, Size};
+    use crate::paint::gradient;
+    use crate::render::frame::BorderStyle;
+    use crate::{geom, Transform};
+    use std::sync::Arc;
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const GREEN: [u8; 4] = [0, 255, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+    const CLEAR: [u8; 4] = [0, 0, 0, 0];
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+    fn color(c: [u8; 4]) -> Color {
+        Color::new(c[0], c[1], c[2], c[3])
+    }
+
+    fn rgba(color: Color) -> [u8; 4] {
+        [color.red(), color.green(), color.blue(), color.alpha()]
+    }
+
+    fn rect(x: f32, y: f32, w: f32, h: f32, c: [u8; 4], border: Option<Border>) -> Node {
+        Node::Rect {
+            rect: FRect::new(x, y, w, h),
+            paint: Paint::Solid(color(c)),
+            radius: 0f32.into(),
+            border,
+        }
+    }
+
+    fn render(node: &Node) -> SoftRenderer {
+        let mut renderer = SoftRenderer::new(Size(10, 10));
+        renderer.render_node(node, &WalkState::default());
+        renderer
+    }
+
+    #[test]
+    fn fill() {
+        let r = render(&rect(2f32, 3f32, 4f32, 5f32, RED, None));
+        for y in 0..10 {
+            for x in 0..10 {
+                let inside = x >= 2 && x < 6 && y >= 3 && y < 8;
+                let expected = if inside { RED } else { CLEAR };
+                assert_eq!(rgba(r.pixel(x, y)), expected, "pixel {}, {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn fill_samples_pixel_centers() {
+        // covers the centers of pixels 2 to 4 only
+        let r = render(&rect(1.6f32, 0f32, 3f32, 1f32, RED, None));
+        assert_eq!(rgba(r.pixel(1, 0)), CLEAR);
+        assert_eq!(rgba(r.pixel(2, 0)), RED);
+        assert_eq!(rgba(r.pixel(4, 0)), RED);
+        assert_eq!(rgba(r.pixel(5, 0)), CLEAR);
+    }
+
+    #[test]
+    fn fill_blends_and_fades() {
+        let node = Node::Group(vec![
+            rect(0f32, 0f32, 10f32, 10f32, RED, None),
+            rect(0f32, 0f32, 5f32, 10f32, BLUE, None).with_opacity(0.5f32),
+        ]);
+        let r = render(&node);
+        assert_eq!(rgba(r.pixel(2, 2)), rgba(color(BLUE).scale_alpha(0.5f32).over(color(RED))));
+        assert_eq!(rgba(r.pixel(7, 2)), RED);
+    }
+
+    fn gradient_reds(lut: Option<usize>, interpolation: Interpolation) -> Vec<u8> {
+        let node = Node::Rect {
+            rect: FRect::new(0f32, 0f32, 10f32, 10f32),
+            paint: Paint::linear_gradient(
+                vec![(0f32, color(BLACK)), (1f32, color(WHITE))],
+                gradient::Direction::E,
+            ),
+            radius: 0f32.into(),
+            border: None,
+        };
+        let mut r = SoftRenderer::new(Size(10, 10));
+        r.set_gradient_lut(lut);
+        r.set_gradient_interpolation(interpolation);
+        r.render_node(&node, &WalkState::default());
+        (0..10).map(|x| r.pixel(x, 5).red()).collect()
+    }
+
+    #[test]
+    fn gradient_lut_keeps_colors() {
+        for &interpolation in &[Interpolation::Srgb, Interpolation::Linear] {
+            let exact = gradient_reds(None, interpolation);
+            let cached = gradient_reds(Some(256), interpolation);
+            for (e, c) in exact.iter().zip(cached.iter()) {
+                assert!((*e as i32 - *c as i32).abs() <= 1, "{:?} != {:?}", exact, cached);
+            }
+        }
+        // the middle pixels are lighter when interpolated in linear light
+        let srgb = gradient_reds(None, Interpolation::Srgb);
+        let linear = gradient_reds(None, Interpolation::Linear);
+        assert!(linear[4] > srgb[4] && linear[5] > srgb[5]);
+    }
+
+    /// A black to white radial gradient over a 8x8 rect whose top-left corner and
+    /// center fall on the centers of pixels (0, 0) and (4, 4)
+    fn radial(radius: f32) -> SoftRenderer {
+        let spec = gradient::RadialSpec {
+            radius,
+            ..gradient::RadialSpec::default()
+        };
+        let node = Node::Rect {
+            rect: FRect::new(0.5f32, 0.5f32, 8f32, 8f32),
+            paint: Paint::radial_gradient(vec![(0f32, color(BLACK)), (1f32, color(WHITE))], spec),
+            radius: 0f32.into(),
+            border: None,
+        };
+        render(&node)
+    }
+
+    #[test]
+    fn radial_gradient_reach() {
+        let stops = [Stop(0f32, color(BLACK)), Stop(1f32, color(WHITE))];
+        let r = radial(1f32);
+        // the last stop is reached at the farthest corner
+        assert_eq!(rgba(r.pixel(4, 4)), BLACK);
+        assert_eq!(rgba(r.pixel(0, 0)), WHITE);
+        let mid = rgba(gradient::color_at(&stops, 0.5f32));
+        assert_eq!(rgba(r.pixel(2, 2)), mid);
+        assert_eq!(rgba(r.pixel(6, 6)), mid);
+        assert_eq!(rgba(r.pixel(2, 6)), mid);
+
+        // and at half that distance with a radius of 0.5
+        let r = radial(0.5f32);
+        assert_eq!(rgba(r.pixel(4, 4)), BLACK);
+        assert_eq!(rgba(r.pixel(2, 2)), WHITE);
+        assert_eq!(rgba(r.pixel(0, 0)), WHITE);
+        assert_eq!(rgba(r.pixel(3, 3)), mid);
+    }
+
+    #[test]
+    fn rounded_corners() {
+        let node = Node::Rect {
+            rect: FRect::new(0f32, 0f32, 10f32, 10f32),
+            paint: Paint::Solid(color(RED)),
+            radius: 4f32.into(),
+            border: None,
+        };
+        let r = render(&node);
+        for &(x, y) in &[(0, 0), (9, 0), (9, 9), (0, 9)] {
+            assert_eq!(rgba(r.pixel(x, y)), CLEAR, "corner {}, {}", x, y);
+        }
+        assert_eq!(rgba(r.pixel(4, 0)), RED);
+        assert_eq!(rgba(r.pixel(2, 2)), RED);
+    }
+
+    #[test]
+    fn border() {
+        let border = Border::from((color(GREEN), 2f32));
+        let r = render(&rect(0f32, 0f32, 10f32, 10f32, RED, Some(border)));
+        for &(x, y) in &[(0, 0), (1, 5), (5, 1), (8, 5), (9, 9), (5, 8)] {
+            assert_eq!(rgba(r.pixel(x, y)), GREEN, "border {}, {}", x, y);
+        }
+        for &(x, y) in &[(2, 2), (5, 5), (7, 7), (2, 7)] {
+            assert_eq!(rgba(r.pixel(x, y)), RED, "inside {}, {}", x, y);
+        }
+    }
+
+    #[test]
+    fn dashed_border() {
+        let style = BorderStyle::Dashed {
+            dash: 3f32,
+            gap: 2f32,
+        };
+        let border = Border::new(color(GREEN), 1f32, style);
+        let r = render(&rect(0f32, 0f32, 10f32, 10f32, RED, Some(border)));
+        // along the top edge, the arc length is x
+        let top: Vec<[u8; 4]> = (0..10).map(|x| rgba(r.pixel(x, 0))).collect();
+        assert_eq!(top, vec![GREEN, GREEN, GREEN, RED, RED, GREEN, GREEN, GREEN, RED, RED]);
+    }
+
+    #[test]
+    fn text() {
+        let node = Node::Text {
+            rect: FRect::new(1f32, 0f32, 6f32, 12f32),
+            text: "I".to_string(),
+            font_size: 10f32,
+            color: color(BLUE),
+        };
+        let r = render(&node);
+        // the bar of the 'I' is on the third column of the glyph
+        for y in 2..9 {
+            assert_eq!(rgba(r.pixel(3, y)), BLUE, "row {}", y);
+            assert_eq!(rgba(r.pixel(1, y)), CLEAR, "row {}", y);
+        }
+        assert_eq!(rgba(r.pixel(3, 1)), CLEAR);
+        assert_eq!(rgba(r.pixel(3, 9)), CLEAR);
+    }
+
+    #[test]
+    fn image_src() {
+        // 4x4 image whose red and green encode the pixel coordinates
+        let data = (0..4)
+            .flat_map(|y| (0..4).flat_map(move |x| vec![x * 60, y * 60, 0, 255]))
+            .collect();
+        let image = Arc::new(Image::new(Size(4, 4), data));
+        // top-right quarter of the image, drawn twice its size
+        let r = render(&Node::Image {
+            rect: FRect::new(0f32, 0f32, 4f32, 4f32),
+            image,
+            src: FRect::new(2f32, 0f32, 2f32, 2f32),
+        });
+        for y in 0..4 {
+            for x in 0..4 {
+                let expected = [(2 + x / 2) as u8 * 60, (y / 2) as u8 * 60, 0, 255];
+                assert_eq!(rgba(r.pixel(x, y)), expected, "pixel {}, {}", x, y);
+            }
+        }
+        assert_eq!(rgba(r.pixel(4, 0)), CLEAR);
+        assert_eq!(rgba(r.pixel(0, 4)), CLEAR);
+    }
+
+    fn shadow(rect: FRect, radius: f32, blur: f32, offset: geom::FVec) -> Node {
+        Node::Shadow {
+            rect,
+            radius,
+            blur,
+            color: color(BLACK),
+            offset,
+        }
+    }
+
+    #[test]
+    fn shadow_fades_over_blur() {
+        let area = FRect::new(2f32, 2f32, 6f32, 6f32);
+        let r = render(&shadow(area, 0f32, 4f32, geom::Vec(0f32, 0f32)));
+        // opaque well inside, and faded out at half the blur outside
+        assert_eq!(rgba(r.pixel(4, 5)), BLACK);
+        let alphas: Vec<u8> = (0..10).map(|x| r.pixel(x, 5).alpha()).collect();
+        assert!(alphas[0] < alphas[1] && alphas[1] < alphas[2], "{:?}", alphas);
+        assert!(alphas.iter().eq(alphas.iter().rev()), "{:?}", alphas);
+        // the edge passes between pixels 1 and 2, 0.5 px from their centers
+        let near = |a: u8, coverage: f32| (a as f32 - 255f32 * coverage).abs() <= 1f32;
+        assert!(near(alphas[0], 0.125f32), "{:?}", alphas);
+        assert!(near(alphas[1], 0.375f32), "{:?}", alphas);
+        assert!(near(alphas[2], 0.625f32), "{:?}", alphas);
+        assert!(near(alphas[3], 0.875f32), "{:?}", alphas);
+    }
+
+    #[test]
+    fn shadow_offset_and_corners() {
+        let area = FRect::new(0f32, 0f32, 6f32, 6f32);
+        let r = render(&shadow(area, 3f32, 0f32, geom::Vec(2f32, 3f32)));
+        assert_eq!(rgba(r.pixel(1, 5)), CLEAR);
+        assert_eq!(rgba(r.pixel(5, 6)), BLACK);
+        assert_eq!(rgba(r.pixel(7, 6)), BLACK);
+        assert_eq!(rgba(r.pixel(8, 9)), CLEAR);
+        // rounded corners
+        assert_eq!(rgba(r.pixel(2, 3)), CLEAR);
+        assert_eq!(rgba(r.pixel(7, 3)), CLEAR);
+        assert_eq!(rgba(r.pixel(7, 8)), CLEAR);
+    }
+
+    #[test]
+    fn shadow_behind_rect() {
+        let area = FRect::new(2f32, 2f32, 4f32, 4f32);
+        let node = Node::Group(vec![
+            shadow(area, 0f32, 0f32, geom::Vec(2f32, 2f32)),
+            rect(2f32, 2f32, 4f32, 4f32, RED, None),
+        ]);
+        let r = render(&node);
+        assert_eq!(rgba(r.pixel(3, 3)), RED);
+        assert_eq!(rgba(r.pixel(5, 5)), RED);
+        assert_eq!(rgba(r.pixel(7, 7)), BLACK);
+        assert_eq!(rgba(r.pixel(7, 3)), CLEAR);
+    }
+
+    #[test]
+    fn clip() {
+        let node = Node::Clip(
+            FRect::new(3f32, 3f32, 4f32, 2f32),
+            Box::new(rect(0f32, 0f32, 10f32, 10f32, RED, None)),
+        );
+        let r = render(&node);
+        for y in 0..10 {
+            for x in 0..10 {
+                let inside = x >= 3 && x < 7 && y >= 3 && y < 5;
+                let expected = if inside { RED } else { CLEAR };
+                assert_eq!(rgba(r.pixel(x, y)), expected, "pixel {}, {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn clip_is_transformed() {
+        let clip = Node::Clip(
+            FRect::new(0f32, 0f32, 2f32, 2f32),
+            Box::new(rect(0f32, 0f32, 10f32, 10f32, RED, None)),
+        );
+        let node = Node::transform(clip, &Transform::translation(geom::Vec(5f32, 5f32)));
+        let r = render(&node);
+        assert_eq!(rgba(r.pixel(4, 4)), CLEAR);
+        assert_eq!(rgba(r.pixel(5, 5)), RED);
+        assert_eq!(rgba(r.pixel(6, 6)), RED);
+        assert_eq!(rgba(r.pixel(7, 7)), CLEAR);
+    }
+
+    #[test]
+    fn frame_clear_and_scale() {
+        let frame = Frame::new(
+            unsafe { winit::WindowId::dummy() },
+            IRect::new(0, 0, 10, 10),
+            2f32,
+            Some(color(BLUE)),
+            Some(rect(1f32, 1f32, 2f32, 2f32, RED, None)),
+        );
+        let mut r = SoftRenderer::new(Size(10, 10));
+        r.render(&frame);
+        assert_eq!(rgba(r.pixel(1, 1)), BLUE);
+        assert_eq!(rgba(r.pixel(2, 2)), RED);
+        assert_eq!(rgba(r.pixel(5, 5)), RED);
+        assert_eq!(rgba(r.pixel(6, 6)), BLUE);
+    }
+}
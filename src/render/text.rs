@@ -0,0 +1,242 @@
+use crate::geom::{FPoint, FSize, Size};
+
+/// Advance of a character, as a fraction of the font size
+const ADVANCE: f32 = 0.6;
+/// Height of a line, as a fraction of the font size
+const LINE_HEIGHT: f32 = 1.2;
+/// Dots of a char cell of the bitmap font along X and Y.
+/// Dots are square as the line height is twice the advance.
+const CELL_DOTS: (usize, usize) = (6, 12);
+/// Size in dots of the glyphs of the bitmap font
+const GLYPH_SIZE: (usize, usize) = (5, 7);
+/// First row of the glyphs in the cell
+const GLYPH_TOP: usize = 2;
+
+/// Measure `s` as if rendered with a monospace font of `px` size.
+/// This is a stand-in for real font metrics, until text is rendered with fonts.
+/// Each char takes the same advance, and each line separated by '\n' the same height.
+/// The width is that of the longest line. An empty string measures zero.
+pub fn measure_ascii(s: &str, px: f32) -> FSize {
+    if s.is_empty() {
+        return Size(0f32, 0f32);
+    }
+    let mut lines = 0;
+    let mut longest = 0;
+    for line in s.split('\n') {
+        lines += 1;
+        longest = longest.max(line.chars().count());
+    }
+    Size(
+        longest as f32 * ADVANCE * px,
+        lines as f32 * LINE_HEIGHT * px,
+    )
+}
+
+/// Whether the text `s` rendered with a monospace bitmap font of `px` size covers `p`,
+/// given relative to the top-left corner of the text.
+/// Each char is drawn in a cell of the advance and line height of `measure_ascii`,
+/// divided in 6 by 12 square dots, where its 5 by 7 glyph is drawn from the third row.
+/// Chars outside of the printable ASCII range are drawn as '?'.
+pub fn ascii_covers(s: &str, px: f32, p: FPoint) -> bool {
+    if px <= 0f32 || p.0 < 0f32 || p.1 < 0f32 {
+        return false;
+    }
+    let dot = px * ADVANCE / CELL_DOTS.0 as f32;
+    let col = (p.0 / dot) as usize;
+    let row = (p.1 / dot) as usize;
+    let (glyph_col, glyph_row) = (col % CELL_DOTS.0, row % CELL_DOTS.1);
+    if glyph_col >= GLYPH_SIZE.0 || !(GLYPH_TOP..GLYPH_TOP + GLYPH_SIZE.1).contains(&glyph_row) {
+        return false;
+    }
+    let c = s
+        .split('\n')
+        .nth(row / CELL_DOTS.1)
+        .and_then(|line| line.chars().nth(col / CELL_DOTS.0));
+    match c {
+        Some(c) => glyph(c)[glyph_col] & (1 << (glyph_row - GLYPH_TOP)) != 0,
+        None => false,
+    }
+}
+
+/// The columns of the glyph of `c`, from left to right, the lowest bit being the top row
+fn glyph(c: char) -> [u8; GLYPH_SIZE.0] {
+    let i = c as usize;
+    if (0x20..0x7f).contains(&i) {
+        GLYPHS[i - 0x20]
+    } else {
+        GLYPHS['?' as usize - 0x20]
+    }
+}
+
+/// 5 by 7 glyphs of the printable ASCII chars, from ' ' to '~'
+#[rustfmt::skip]
+const GLYPHS: [[u8; GLYPH_SIZE.0]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x00, 0x00, 0x5F, 0x00, 0x00], // '!'
+    [0x00, 0x07, 0x00, 0x07, 0x00], // '"'
+    [0x14, 0x7F, 0x14, 0x7F, 0x14], // '#'
+    [0x24, 0x2A, 0x7F, 0x2A, 0x12], // '$'
+    [0x23, 0x13, 0x08, 0x64, 0x62], // '%'
+    [0x36, 0x49, 0x55, 0x22, 0x50], // '&'
+    [0x00, 0x05, 0x03, 0x00, 0x00], // '\''
+    [0x00, 0x1C, 0x22, 0x41, 0x00], // '('
+    [0x00, 0x41, 0x22, 0x1C, 0x00], // ')'
+    [0x08, 0x2A, 0x1C, 0x2A, 0x08], // '*'
+    [0x08, 0x08, 0x3E, 0x08, 0x08], // '+'
+    [0x00, 0x50, 0x30, 0x00, 0x00], // ','
+    [0x08, 0x08, 0x08, 0x08, 0x08], // '-'
+    [0x00, 0x60, 0x60, 0x00, 0x00], // '.'
+    [0x20, 0x10, 0x08, 0x04, 0x02], // '/'
+    [0x3E, 0x51, 0x49, 0x45, 0x3E], // '0'
+    [0x00, 0x42, 0x7F, 0x40, 0x00], // '1'
+    [0x42, 0x61, 0x51, 0x49, 0x46], // '2'
+    [0x21, 0x41, 0x45, 0x4B, 0x31], // '3'
+    [0x18, 0x14, 0x12, 0x7F, 0x10], // '4'
+    [0x27, 0x45, 0x45, 0x45, 0x39], // '5'
+    [0x3C, 0x4A, 0x49, 0x49, 0x30], // '6'
+    [0x01, 0x71, 0x09, 0x05, 0x03], // '7'
+    [0x36, 0x49, 0x49, 0x49, 0x36], // '8'
+    [0x06, 0x49, 0x49, 0x29, 0x1E], // '9'
+    [0x00, 0x36, 0x36, 0x00, 0x00], // ':'
+    [0x00, 0x56, 0x36, 0x00, 0x00], // ';'
+    [0x08, 0x14, 0x22, 0x41, 0x00], // '<'
+    [0x14, 0x14, 0x14, 0x14, 0x14], // '='
+    [0x00, 0x41, 0x22, 0x14, 0x08], // '>'
+    [0x02, 0x01, 0x51, 0x09, 0x06], // '?'
+    [0x32, 0x49, 0x79, 0x41, 0x3E], // '@'
+    [0x7E, 0x11, 0x11, 0x11, 0x7E], // 'A'
+    [0x7F, 0x49, 0x49, 0x49, 0x36], // 'B'
+    [0x3E, 0x41, 0x41, 0x41, 0x22], // 'C'
+    [0x7F, 0x41, 0x41, 0x22, 0x1C], // 'D'
+    [0x7F, 0x49, 0x49, 0x49, 0x41], // 'E'
+    [0x7F, 0x09, 0x09, 0x01, 0x01], // 'F'
+    [0x3E, 0x41, 0x41, 0x51, 0x32], // 'G'
+    [0x7F, 0x08, 0x08, 0x08, 0x7F], // 'H'
+    [0x00, 0x41, 0x7F, 0x41, 0x00], // 'I'
+    [0x20, 0x40, 0x41, 0x3F, 0x01], // 'J'
+    [0x7F, 0x08, 0x14, 0x22, 0x41], // 'K'
+    [0x7F, 0x40, 0x40, 0x40, 0x40], // 'L'
+    [0x7F, 0x02, 0x04, 0x02, 0x7F], // 'M'
+    [0x7F, 0x04, 0x08, 0x10, 0x7F], // 'N'
+    [0x3E, 0x41, 0x41, 0x41, 0x3E], // 'O'
+    [0x7F, 0x09, 0x09, 0x09, 0x06], // 'P'
+    [0x3E, 0x41, 0x51, 0x21, 0x5E], // 'Q'
+    [0x7F, 0x09, 0x19, 0x29, 0x46], // 'R'
+    [0x46, 0x49, 0x49, 0x49, 0x31], // 'S'
+    [0x01, 0x01, 0x7F, 0x01, 0x01], // 'T'
+    [0x3F, 0x40, 0x40, 0x40, 0x3F], // 'U'
+    [0x1F, 0x20, 0x40, 0x20, 0x1F], // 'V'
+    [0x7F, 0x20, 0x18, 0x20, 0x7F], // 'W'
+    [0x63, 0x14, 0x08, 0x14, 0x63], // 'X'
+    [0x03, 0x04, 0x78, 0x04, 0x03], // 'Y'
+    [0x61, 0x51, 0x49, 0x45, 0x43], // 'Z'
+    [0x00, 0x7F, 0x41, 0x41, 0x00], // '['
+    [0x02, 0x04, 0x08, 0x10, 0x20], // '\\'
+    [0x00, 0x41, 0x41, 0x7F, 0x00], // ']'
+    [0x04, 0x02, 0x01, 0x02, 0x04], // '^'
+    [0x40, 0x40, 0x40, 0x40, 0x40], // '_'
+    [0x00, 0x01, 0x02, 0x04, 0x00], // '`'
+    [0x20, 0x54, 0x54, 0x54, 0x78], // 'a'
+    [0x7F, 0x48, 0x44, 0x44, 0x38], // 'b'
+    [0x38, 0x44, 0x44, 0x44, 0x20], // 'c'
+    [0x38, 0x44, 0x44, 0x48, 0x7F], // 'd'
+    [0x38, 0x54, 0x54, 0x54, 0x18], // 'e'
+    [0x08, 0x7E, 0x09, 0x01, 0x02], // 'f'
+    [0x08, 0x14, 0x54, 0x54, 0x3C], // 'g'
+    [0x7F, 0x08, 0x04, 0x04, 0x78], // 'h'
+    [0x00, 0x44, 0x7D, 0x40, 0x00], // 'i'
+    [0x20, 0x40, 0x44, 0x3D, 0x00], // 'j'
+    [0x00, 0x7F, 0x10, 0x28, 0x44], // 'k'
+    [0x00, 0x41, 0x7F, 0x40, 0x00], // 'l'
+    [0x7C, 0x04, 0x18, 0x04, 0x78], // 'm'
+    [0x7C, 0x08, 0x04, 0x04, 0x78], // 'n'
+    [0x38, 0x44, 0x44, 0x44, 0x38], // 'o'
+    [0x7C, 0x14, 0x14, 0x14, 0x08], // 'p'
+    [0x08, 0x14, 0x14, 0x18, 0x7C], // 'q'
+    [0x7C, 0x08, 0x04, 0x04, 0x08], // 'r'
+    [0x48, 0x54, 0x54, 0x54, 0x20], // 's'
+    [0x04, 0x3F, 0x44, 0x40, 0x20], // 't'
+    [0x3C, 0x40, 0x40, 0x20, 0x7C], // 'u'
+    [0x1C, 0x20, 0x40, 0x20, 0x1C], // 'v'
+    [0x3C, 0x40, 0x30, 0x40, 0x3C], // 'w'
+    [0x44, 0x28, 0x10, 0x28, 0x44], // 'x'
+    [0x0C, 0x50, 0x50, 0x50, 0x3C], // 'y'
+    [0x44, 0x64, 0x54, 0x4C, 0x44], // 'z'
+    [0x00, 0x08, 0x36, 0x41, 0x00], // '{'
+    [0x00, 0x00, 0x7F, 0x00, 0x00], // '|'
+    [0x00, 0x41, 0x36, 0x08, 0x00], // '}'
+    [0x02, 0x01, 0x02, 0x04, 0x02], // '~'
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::Point;
+
+    #[test]
+    fn measure_empty() {
+        assert_eq!(measure_ascii("", 10f32), Size(0f32, 0f32));
+    }
+
+    #[test]
+    fn measure_single_line() {
+        assert_eq!(measure_ascii("Hello", 10f32), Size(30f32, 12f32));
+        // chars are counted, not bytes
+        assert_eq!(measure_ascii("été", 10f32), Size(18f32, 12f32));
+    }
+
+    #[test]
+    fn measure_multi_line() {
+        assert_eq!(measure_ascii("ab\nabcd\nc", 10f32), Size(24f32, 36f32));
+        // a trailing newline starts an empty line
+        assert_eq!(measure_ascii("ab\n", 10f32), Size(12f32, 24f32));
+    }
+
+    /// Whether the dot at `col`, `row` is covered, with a font size giving 1 px dots
+    fn dot(s: &str, col: usize, row: usize) -> bool {
+        ascii_covers(s, 10f32, Point(col as f32 + 0.5f32, row as f32 + 0.5f32))
+    }
+
+    #[test]
+    fn glyph_dots() {
+        // 'I' is a vertical bar with serifs on the top and bottom rows
+        for row in 2..9 {
+            assert!(dot("I", 2, row), "row {}", row);
+            assert!(!dot("I", 0, row), "row {}", row);
+        }
+        assert!(dot("I", 1, 2));
+        assert!(!dot("I", 1, 4));
+        assert!(dot("I", 3, 8));
+        // margins above and below the glyph, and spacing after it
+        assert!(!dot("I", 2, 1));
+        assert!(!dot("I", 2, 9));
+        assert!(!dot("I", 5, 5));
+    }
+
+    #[test]
+    fn chars_and_lines() {
+        let s = "-I\nI";
+        // '-' is drawn on the fourth row of the glyph
+        assert!(dot(s, 0, 5));
+        assert!(!dot(s, 0, 4));
+        // the second char is one advance on the right
+        assert!(dot(s, 8, 5));
+        // the second line is one line height below
+        assert!(dot(s, 2, 14));
+        // nothing past the end of a line or the last line
+        assert!(!dot(s, 8, 14));
+        assert!(!dot(s, 2, 26));
+    }
+
+    #[test]
+    fn covers_outside_and_unknown() {
+        assert!(!ascii_covers("I", 10f32, Point(-0.5f32, 5f32)));
+        assert!(!ascii_covers("I", 0f32, Point(2.5f32, 5.5f32)));
+        assert!(!dot(" ", 2, 5));
+        for row in 0..12 {
+            for col in 0..6 {
+                assert_eq!(dot("é", col, row), dot("?", col, row));
+            }
+        }
+    }
+}
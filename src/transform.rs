@@ -36,14 +36,18 @@ impl Transform {
         ])
     }
 
+    /// Rotation matrix, rotating in the same direction as `rotate`: from the X axis
+    /// towards the Y axis, that is clockwise on screen where Y points downwards.
+    /// `Transform::rotation(radians)` equals `Transform::identity().rotate(radians)`.
+    /// It used to build the inverse rotation; callers relying on it must negate the angle.
     #[rustfmt::skip]
     #[inline(always)]
     pub fn rotation(radians: f32) -> Transform {
         let sin = radians.sin();
         let cos = radians.cos();
         Transform([
-            [   cos, sin, 0f32,     ],
-            [   -sin, cos, 0f32,    ],
+            [   cos, -sin, 0f32,    ],
+            [   sin, cos, 0f32,     ],
         ])
     }
 
@@ -56,6 +60,54 @@ impl Transform {
         ])
     }
 
+    /// Same as `homothetie`
+    #[inline(always)]
+    pub fn scaling(factor: [f32; 2]) -> Transform {
+        Transform::homothetie(factor)
+    }
+
+    /// Shear matrix. x' = x + tan(x_radians) * y and y' = tan(y_radians) * x + y
+    #[rustfmt::skip]
+    #[inline(always)]
+    pub fn shear(x_radians: f32, y_radians: f32) -> Transform {
+        let tx = x_radians.tan();
+        let ty = y_radians.tan();
+        Transform([
+            [   1f32, tx, 0f32, ],
+            [   ty, 1f32, 0f32, ],
+        ])
+    }
+
+    /// Build a transform that scales, then rotates, then translates.
+    /// That is `Transform::homothetie(scale).rotate(radians).translate(translate)`.
+    #[inline(always)]
+    pub fn from_srt(scale: [f32; 2], radians: f32, translate: FVec) -> Transform {
+        Transform::homothetie(scale)
+            .rotate(radians)
+            .translate(translate)
+    }
+
+    /// Determinant of the linear part of the transform
+    #[inline(always)]
+    pub fn determinant(&self) -> f32 {
+        self[(0, 0)] * self[(1, 1)] - self[(0, 1)] * self[(1, 0)]
+    }
+
+    /// Inverse transform, or `None` if the transform is not invertible
+    #[rustfmt::skip]
+    pub fn inverse(&self) -> Option<Transform> {
+        let det = self.determinant();
+        if det.abs() <= std::f32::EPSILON {
+            return None;
+        }
+        let [[a, b, c], [d, e, f]] = self.0;
+        let inv = 1f32 / det;
+        Some(Transform::new(
+            e * inv, -b * inv, (b * f - c * e) * inv,
+            -d * inv, a * inv, (c * d - a * f) * inv,
+        ))
+    }
+
     #[rustfmt::skip]
     #[inline(always)]
     pub fn translate(&self, vec: FVec) -> Transform {
@@ -81,6 +133,12 @@ impl Transform {
         )
     }
 
+    /// Apply a shear after self (see `Transform::shear`)
+    #[inline(always)]
+    pub fn skew(&self, x_radians: f32, y_radians: f32) -> Transform {
+        Transform::shear(x_radians, y_radians) * *self
+    }
+
     #[rustfmt::skip]
     #[inline(always)]
     pub fn scale(&self, factors: [f32; 2]) -> Transform {
@@ -90,6 +148,29 @@ impl Transform {
             y * self[(1, 0)], y * self[(1, 1)], y * self[(1, 2)],
         )
     }
+
+    /// Expand the transform into a 4x4 matrix in column-major order,
+    /// as expected by shader uniforms. Z is left untouched.
+    #[rustfmt::skip]
+    pub fn to_4x4_col_major(&self) -> [f32; 16] {
+        let [[a, b, c], [d, e, f]] = self.0;
+        [
+            a,      d,      0f32,   0f32,
+            b,      e,      0f32,   0f32,
+            0f32,   0f32,   1f32,   0f32,
+            c,      f,      0f32,   1f32,
+        ]
+    }
+
+    /// Extract the 2D transform from a 4x4 matrix in column-major order.
+    /// The Z and projective components are ignored.
+    #[rustfmt::skip]
+    pub fn from_4x4_col_major(m: &[f32; 16]) -> Transform {
+        Transform::new(
+            m[0], m[4], m[12],
+            m[1], m[5], m[13],
+        )
+    }
 }
 
 impl Index<usize> for Transform {
@@ -135,8 +216,8 @@ impl Mul<Transform> for Transform {
             self[0][0] * rhs[0][1] + self[0][1] * rhs[1][1],
             self[0][0] * rhs[0][2] + self[0][1] * rhs[1][2] + self[0][2],
             self[1][0] * rhs[0][0] + self[1][1] * rhs[1][0],
-            self[1][0] * rhs[0][0] + self[1][1] * rhs[1][0],
-            self[1][0] * rhs[0][0] + self[1][1] * rhs[1][0] + self[1][2],
+            self[1][0] * rhs[0][1] + self[1][1] * rhs[1][1],
+            self[1][0] * rhs[0][2] + self[1][1] * rhs[1][2] + self[1][2],
         )
     }
 }
@@ -164,3 +245,91 @@ impl Mul<FPoint> for Transform {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn approx_pt(a: FPoint, b: FPoint) -> bool {
+        (a.0 - b.0).abs() < 1e-4 && (a.1 - b.1).abs() < 1e-4
+    }
+
+    fn approx(a: &Transform, b: &Transform) -> bool {
+        a.0.iter()
+            .zip(b.0.iter())
+            .all(|(ra, rb)| ra.iter().zip(rb.iter()).all(|(ea, eb)| (ea - eb).abs() < 1e-4))
+    }
+
+    #[test]
+    fn mul_composes_right_to_left() {
+        let t = Transform::translation(Vec(3f32, -2f32));
+        let r = Transform::rotation(PI / 3f32);
+        let p = Point(1.5f32, 4f32);
+        assert!(approx_pt((t * r) * p, t * (r * p)));
+        assert!(approx_pt((r * t) * p, r * (t * p)));
+    }
+
+    #[test]
+    fn mul_identity() {
+        let m = Transform::new(1f32, 2f32, 3f32, 4f32, 5f32, 6f32);
+        assert_eq!(m * Transform::identity(), m);
+        assert_eq!(Transform::identity() * m, m);
+    }
+
+    #[test]
+    fn inverse() {
+        let m = Transform::identity()
+            .scale([2f32, 0.5f32])
+            .rotate(0.7f32)
+            .translate(Vec(10f32, -4f32));
+        let inv = m.inverse().unwrap();
+        assert!(approx(&(m * inv), &Transform::identity()));
+        assert!(approx(&(inv * m), &Transform::identity()));
+    }
+
+    #[test]
+    fn inverse_singular() {
+        let m = Transform::new(1f32, 2f32, 5f32, 2f32, 4f32, -1f32);
+        assert_eq!(m.determinant(), 0f32);
+        assert!(m.inverse().is_none());
+        assert!(Transform::homothetie([0f32, 1f32]).inverse().is_none());
+    }
+
+    #[test]
+    fn shear() {
+        let s = Transform::shear(PI / 4f32, 0f32);
+        assert!(approx_pt(s * Point(1f32, 2f32), Point(3f32, 2f32)));
+        let s = Transform::shear(0f32, PI / 4f32);
+        assert!(approx_pt(s * Point(1f32, 2f32), Point(1f32, 3f32)));
+    }
+
+    #[test]
+    fn from_srt() {
+        let m = Transform::from_srt([2f32, 3f32], PI / 2f32, Vec(5f32, 7f32));
+        let p = Point(1f32, 1f32);
+        let scaled = Transform::homothetie([2f32, 3f32]) * p;
+        let rotated = Transform::identity().rotate(PI / 2f32) * scaled;
+        let expected = Transform::translation(Vec(5f32, 7f32)) * rotated;
+        assert!(approx_pt(m * p, expected));
+        assert!(approx_pt(m * p, Point(2f32, 9f32)));
+    }
+
+    #[test]
+    fn rotation_matches_rotate() {
+        let p = Point(2f32, -1f32);
+        for &a in &[0f32, 0.3f32, PI / 2f32, 2f32, -1.2f32] {
+            let expected = Transform::identity().rotate(a) * p;
+            assert!(approx_pt(Transform::rotation(a) * p, expected));
+        }
+        assert!(approx_pt(Transform::rotation(PI / 2f32) * Point(1f32, 0f32), Point(0f32, 1f32)));
+    }
+
+    #[test]
+    fn skew() {
+        let m = Transform::translation(Vec(1f32, 2f32));
+        let s = m.skew(PI / 4f32, 0f32);
+        assert!(approx(&s, &(Transform::shear(PI / 4f32, 0f32) * m)));
+        assert!(approx_pt(s * Point(0f32, 0f32), Point(3f32, 2f32)));
+    }
+}
@@ -0,0 +1,265 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Callback advancing an animation by the time elapsed since the previous tick.
+/// It returns whether the animation is still running.
+pub type Animator = Box<dyn FnMut(Duration) -> bool>;
+
+/// Drives the registered animators. The event loop ticks it at each iteration
+/// while an animator is active, and waits for events otherwise.
+/// Clones of the scheduler share the same animators, so that one can be kept
+/// by the application, e.g. to start an animation in a click callback.
+#[derive(Clone, Default)]
+pub struct AnimationScheduler {
+    animators: Rc<RefCell<Vec<Animator>>>,
+}
+
+impl AnimationScheduler {
+    pub fn new() -> AnimationScheduler {
+        AnimationScheduler::default()
+    }
+
+    /// Register an animator. It is called at each tick until it returns `false`.
+    /// Animators typically update views, which invalidate their frame.
+    pub fn add<F>(&self, animator: F)
+    where
+        F: FnMut(Duration) -> bool + 'static,
+    {
+        self.animators.borrow_mut().push(Box::new(animator));
+    }
+
+    /// Whether any animator is registered
+    pub fn is_active(&self) -> bool {
+        !self.animators.borrow().is_empty()
+    }
+
+    /// Advance all animators by `dt` and remove the finished ones.
+    /// Animators added during the tick are first called at the next tick.
+    pub fn tick(&self, dt: Duration) {
+        let animators = self.animators.replace(Vec::new());
+        let mut running = Vec::with_capacity(animators.len());
+        for mut animator in animators {
+            if animator(dt) {
+                running.push(animator);
+            }
+        }
+        let mut animators = self.animators.borrow_mut();
+        running.extend(animators.drain(..));
+        *animators = running;
+    }
+}
+
+/// Timing function mapping the progress of an animation to the progress of its value,
+/// with the same curves as the CSS timing functions
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    /// Same as `CubicBezier(0.42, 0, 1, 1)`
+    EaseIn,
+    /// Same as `CubicBezier(0, 0, 0.58, 1)`
+    EaseOut,
+    /// Same as `CubicBezier(0.42, 0, 0.58, 1)`
+    EaseInOut,
+    /// Cubic Bézier curve from (0, 0) to (1, 1) with control points (x1, y1) and (x2, y2).
+    /// x1 and x2 must be in [0, 1] for the curve to be a function of time.
+    CubicBezier(f32, f32, f32, f32),
+}
+
+impl Easing {
+    /// The eased progress for the animation progress `t`, clamped to [0, 1]
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.max(0f32).min(1f32);
+        match *self {
+            Easing::Linear => t,
+            Easing::EaseIn => cubic_bezier(0.42f32, 0f32, 1f32, 1f32, t),
+            Easing::EaseOut => cubic_bezier(0f32, 0f32, 0.58f32, 1f32, t),
+            Easing::EaseInOut => cubic_bezier(0.42f32, 0f32, 0.58f32, 1f32, t),
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, t),
+        }
+    }
+}
+
+impl Default for Easing {
+    fn default() -> Easing {
+        Easing::Linear
+    }
+}
+
+/// Solve the curve for x and give its y, as browsers do: a few Newton iterations
+/// and a bisection if they do not converge
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    const EPSILON: f32 = 1e-6;
+
+    // polynomial coefficients of each coordinate along the curve parameter
+    let cx = 3f32 * x1;
+    let bx = 3f32 * (x2 - x1) - cx;
+    let ax = 1f32 - cx - bx;
+    let cy = 3f32 * y1;
+    let by = 3f32 * (y2 - y1) - cy;
+    let ay = 1f32 - cy - by;
+
+    let curve_x = |s: f32| ((ax * s + bx) * s + cx) * s;
+    let curve_y = |s: f32| ((ay * s + by) * s + cy) * s;
+    let slope_x = |s: f32| (3f32 * ax * s + 2f32 * bx) * s + cx;
+
+    if x <= 0f32 || x >= 1f32 {
+        return x;
+    }
+
+    let mut s = x;
+    for _ in 0..8 {
+        let err = curve_x(s) - x;
+        if err.abs() < EPSILON {
+            return curve_y(s);
+        }
+        let slope = slope_x(s);
+        if slope.abs() < EPSILON {
+            break;
+        }
+        s -= err / slope;
+    }
+
+    let (mut lo, mut hi) = (0f32, 1f32);
+    s = x;
+    while lo < hi {
+        let sx = curve_x(s);
+        if (sx - x).abs() < EPSILON {
+            break;
+        }
+        if x > sx {
+            lo = s;
+        } else {
+            hi = s;
+        }
+        let mid = lo + (hi - lo) / 2f32;
+        if mid == s {
+            break;
+        }
+        s = mid;
+    }
+    curve_y(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn tick_until_completion() {
+        let sched = AnimationScheduler::new();
+        assert!(!sched.is_active());
+
+        let elapsed = Rc::new(Cell::new(ms(0)));
+        let el = elapsed.clone();
+        sched.add(move |dt| {
+            el.set(el.get() + dt);
+            el.get() < ms(100)
+        });
+        assert!(sched.is_active());
+
+        sched.tick(ms(40));
+        sched.tick(ms(40));
+        assert!(sched.is_active());
+        sched.tick(ms(40));
+        assert_eq!(elapsed.get(), ms(120));
+        assert!(!sched.is_active());
+
+        // finished animators are not called anymore
+        sched.tick(ms(40));
+        assert_eq!(elapsed.get(), ms(120));
+    }
+
+    #[test]
+    fn clones_share_animators() {
+        let sched = AnimationScheduler::new();
+        let clone = sched.clone();
+        clone.add(|_| false);
+        assert!(sched.is_active());
+        sched.tick(ms(16));
+        assert!(!clone.is_active());
+    }
+
+    #[test]
+    fn add_during_tick() {
+        let sched = AnimationScheduler::new();
+        let calls = Rc::new(Cell::new(0));
+        let (s, c) = (sched.clone(), calls.clone());
+        sched.add(move |_| {
+            let c = c.clone();
+            s.add(move |_| {
+                c.set(c.get() + 1);
+                true
+            });
+            false
+        });
+
+        sched.tick(ms(16));
+        assert_eq!(calls.get(), 0);
+        assert!(sched.is_active());
+        sched.tick(ms(16));
+        assert_eq!(calls.get(), 1);
+    }
+
+    const EASINGS: [Easing; 6] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+        Easing::CubicBezier(0.1f32, 0.7f32, 0.9f32, 0.3f32),
+        // overshooting curve, as CSS allows for y
+        Easing::CubicBezier(0.3f32, -0.5f32, 0.7f32, 1.5f32),
+    ];
+
+    #[test]
+    fn easing_endpoints() {
+        for easing in EASINGS.iter() {
+            assert_eq!(easing.apply(0f32), 0f32, "{:?}", easing);
+            assert_eq!(easing.apply(1f32), 1f32, "{:?}", easing);
+            assert_eq!(easing.apply(-0.5f32), 0f32, "{:?}", easing);
+            assert_eq!(easing.apply(1.5f32), 1f32, "{:?}", easing);
+        }
+    }
+
+    #[test]
+    fn easing_known_values() {
+        let approx = |a: f32, b: f32| (a - b).abs() < 1e-3;
+        assert_eq!(Easing::Linear.apply(0.3f32), 0.3f32);
+        assert!(approx(Easing::EaseInOut.apply(0.5f32), 0.5f32));
+        // ease-in is slow at start, ease-out fast
+        assert!(Easing::EaseIn.apply(0.25f32) < 0.25f32);
+        assert!(Easing::EaseOut.apply(0.25f32) > 0.25f32);
+        // symmetric control points give a symmetric curve
+        let t = 0.2f32;
+        assert!(approx(Easing::EaseInOut.apply(t), 1f32 - Easing::EaseInOut.apply(1f32 - t)));
+    }
+
+    #[test]
+    fn cubic_bezier_monotonic() {
+        // with y1 and y2 in [0, 1], the eased progress never goes back
+        for easing in EASINGS[..5].iter() {
+            let mut prev = 0f32;
+            for i in 1..=100 {
+                let y = easing.apply(i as f32 / 100f32);
+                assert!(y >= prev - 1e-5, "{:?} at {}: {} < {}", easing, i, y, prev);
+                prev = y;
+            }
+        }
+    }
+
+    #[test]
+    fn cubic_bezier_solves_steep_curve() {
+        // vertical tangents at both ends defeat Newton iterations near the endpoints
+        let easing = Easing::CubicBezier(0f32, 0.5f32, 1f32, 0.5f32);
+        for &x in &[0.001f32, 0.01f32, 0.5f32, 0.99f32, 0.999f32] {
+            let y = easing.apply(x);
+            assert!(y >= 0f32 && y <= 1f32, "{} -> {}", x, y);
+        }
+        assert!((easing.apply(0.5f32) - 0.5f32).abs() < 1e-3);
+    }
+}
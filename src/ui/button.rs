@@ -0,0 +1,334 @@
+use crate::color;
+use crate::geom::{FPoint, FRect, FSize, Size};
+use crate::render::{frame, text};
+use crate::ui::layout::measure_text;
+use crate::ui::view::{self, Base, HasPadding, HasRect, MeasureSpec, View};
+use crate::{ui, Color, Paint};
+
+use std::fmt;
+
+const DEFAULT_FONT_SIZE: f32 = 14f32;
+
+/// A clickable view drawn as a rounded rect with an optional border and text.
+/// The button is drawn darker while pressed and lighter while hovered.
+pub struct Button {
+    common: view::Common,
+    color: Color,
+    radius: frame::CornerRadii,
+    border: Option<frame::Border>,
+    min_size: FSize,
+    text: String,
+    text_color: Color,
+    font_size: f32,
+    pressed: bool,
+    hovered: bool,
+    on_click: Option<Box<dyn FnMut()>>,
+}
+
+impl Button {
+    pub fn new(color: Color) -> Button {
+        Button {
+            common: view::Common::default(),
+            color,
+            radius: 4f32.into(),
+            border: None,
+            min_size: Size(64f32, 24f32),
+            text: String::new(),
+            text_color: Color::from(color::CssName::Black),
+            font_size: DEFAULT_FONT_SIZE,
+            pressed: false,
+            hovered: false,
+            on_click: None,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+        self.common.invalidate(ui::Dirty::FRAME);
+    }
+
+    pub fn radius(&self) -> frame::CornerRadii {
+        self.radius
+    }
+
+    pub fn set_radius<R: Into<frame::CornerRadii>>(&mut self, radius: R) {
+        self.radius = radius.into();
+        self.common.invalidate(ui::Dirty::FRAME);
+    }
+
+    pub fn border(&self) -> Option<frame::Border> {
+        self.border
+    }
+
+    /// Set the border of the button. A solid border can be given as `(Color, f32)`.
+    pub fn set_border<B: Into<frame::Border>>(&mut self, border: Option<B>) {
+        self.border = border.map(Into::into);
+        self.common.invalidate(ui::Dirty::FRAME);
+    }
+
+    pub fn min_size(&self) -> FSize {
+        self.min_size
+    }
+
+    pub fn set_min_size(&mut self, min_size: FSize) {
+        self.min_size = min_size;
+        self.common.invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Set the text drawn centered in the button. No text is drawn if empty.
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+        self.common.invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+
+    pub fn text_color(&self) -> Color {
+        self.text_color
+    }
+
+    pub fn set_text_color(&mut self, color: Color) {
+        self.text_color = color;
+        self.common.invalidate(ui::Dirty::FRAME);
+    }
+
+    pub fn font_size(&self) -> f32 {
+        self.font_size
+    }
+
+    pub fn set_font_size(&mut self, font_size: f32) {
+        self.font_size = font_size;
+        self.common.invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+
+    /// Whether the pointer is currently pressed on the button
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// Whether the pointer is currently over the button
+    pub fn is_hovered(&self) -> bool {
+        self.hovered
+    }
+
+    /// Set the callback invoked when the button is clicked
+    pub fn on_click(&mut self, callback: Box<dyn FnMut()>) {
+        self.on_click = Some(callback);
+    }
+
+    /// Invoke the click callback
+    pub fn click(&mut self) {
+        if let Some(on_click) = self.on_click.as_mut() {
+            on_click();
+        }
+    }
+}
+
+impl Default for Button {
+    fn default() -> Button {
+        Button::new(Color::from(color::CssName::LightGrey))
+    }
+}
+
+impl fmt::Debug for Button {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Button")
+            .field("common", &self.common)
+            .field("color", &self.color)
+            .field("radius", &self.radius)
+            .field("border", &self.border)
+            .field("min_size", &self.min_size)
+            .field("text", &self.text)
+            .field("text_color", &self.text_color)
+            .field("font_size", &self.font_size)
+            .field("pressed", &self.pressed)
+            .field("hovered", &self.hovered)
+            .finish()
+    }
+}
+
+impl View for Button {}
+
+impl view::Measure for Button {
+    fn measure(&mut self, specs: [MeasureSpec; 2]) {
+        let size = measure_text(&self.text, self.font_size, self.padding(), self.min_size, specs);
+        self.set_constrained_measurement(size, specs);
+    }
+}
+
+impl view::Layout for Button {
+    fn layout(&mut self, rect: FRect) {
+        self.common.rect = rect;
+    }
+}
+
+impl view::GestureHandler for Button {
+    fn on_tap(&mut self, pos: FPoint) {
+        // a tap released out of the button, even within the tap radius, is no click
+        if FRect::new_s(0f32, 0f32, self.rect().size()).contains_point(pos) {
+            self.click();
+        }
+    }
+}
+
+impl view::PointerHandler for Button {
+    fn on_pointer_down(&mut self, _pos: FPoint) {
+        self.pressed = true;
+        self.common.invalidate(ui::Dirty::FRAME);
+    }
+
+    fn on_pointer_up(&mut self, _pos: FPoint) {
+        self.pressed = false;
+        self.common.invalidate(ui::Dirty::FRAME);
+    }
+
+    fn on_pointer_enter(&mut self) {
+        self.hovered = true;
+        self.common.invalidate(ui::Dirty::FRAME);
+    }
+
+    fn on_pointer_leave(&mut self) {
+        self.hovered = false;
+        self.common.invalidate(ui::Dirty::FRAME);
+    }
+}
+
+impl view::KeyHandler for Button {}
+
+impl view::FrameRender for Button {
+    fn frame_render(&self) -> Option<frame::Node> {
+        // a pressed button is drawn slightly darker, a hovered one slightly lighter
+        let color = if self.pressed {
+            self.color.lerp(Color::from(color::CssName::Black), 0.2f32)
+        } else if self.hovered {
+            self.color.lerp(Color::from(color::CssName::White), 0.2f32)
+        } else {
+            self.color
+        };
+        let rect = self.rect();
+        let background = frame::Node::Rect {
+            rect,
+            paint: Paint::Solid(color),
+            radius: self.radius,
+            border: self.border,
+        };
+        if self.text.is_empty() {
+            return Some(background);
+        }
+        let size = text::measure_ascii(&self.text, self.font_size);
+        let text = frame::Node::Text {
+            rect: FRect::new_s(
+                rect.x + (rect.width - size.width()) / 2f32,
+                rect.y + (rect.height - size.height()) / 2f32,
+                size,
+            ),
+            text: self.text.clone(),
+            font_size: self.font_size,
+            color: self.text_color,
+        };
+        Some(frame::Node::Group(vec![background, text]))
+    }
+}
+
+impl view::Base for Button {
+    type State = ();
+    type Style = ();
+
+    fn common(&self) -> &view::Common {
+        &self.common
+    }
+    fn common_mut(&mut self) -> &mut view::Common {
+        &mut self.common
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::tests::{move_to, press, release, set_up};
+    use crate::ui::view::{FrameRender, Measurement};
+    use crate::ui::{Node, UserInterface};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// A 64x24 button at the origin, and its click count
+    fn button_ui() -> (Rc<UserInterface>, Rc<Node>, Rc<Cell<u32>>) {
+        let ui = UserInterface::new();
+        let clicks = Rc::new(Cell::new(0));
+        let mut button = Button::default();
+        let count = clicks.clone();
+        button.on_click(Box::new(move || count.set(count.get() + 1)));
+        let node = Node::new(button, ui.clone(), None);
+        set_up(&ui, &node, (200f64, 100f64));
+        (ui, node, clicks)
+    }
+
+    #[test]
+    fn press_release_inside() {
+        let (ui, node, clicks) = button_ui();
+        move_to(&ui, 30f64, 10f64);
+        press(&ui);
+        assert!(node.view_as::<Button>().is_pressed());
+        release(&ui);
+        assert!(!node.view_as::<Button>().is_pressed());
+        assert_eq!(clicks.get(), 1);
+    }
+
+    #[test]
+    fn press_inside_release_outside() {
+        let (ui, node, clicks) = button_ui();
+        move_to(&ui, 60f64, 10f64);
+        press(&ui);
+        // still within the tap radius of the press
+        move_to(&ui, 67f64, 10f64);
+        release(&ui);
+        assert!(!node.view_as::<Button>().is_pressed());
+        assert_eq!(clicks.get(), 0);
+    }
+
+    #[test]
+    fn press_release_outside() {
+        let (ui, _node, clicks) = button_ui();
+        move_to(&ui, 100f64, 50f64);
+        press(&ui);
+        release(&ui);
+        assert_eq!(clicks.get(), 0);
+    }
+
+    #[test]
+    fn hover() {
+        let (ui, node, _clicks) = button_ui();
+        move_to(&ui, 10f64, 10f64);
+        assert!(node.view_as::<Button>().is_hovered());
+        move_to(&ui, 100f64, 10f64);
+        assert!(!node.view_as::<Button>().is_hovered());
+    }
+
+    #[test]
+    fn text() {
+        let ui = UserInterface::new();
+        let mut button = Button::default();
+        button.set_text("a rather long text".into());
+        let node = Node::new(button, ui.clone(), None);
+        set_up(&ui, &node, (400f64, 100f64));
+        let button = node.view_as::<Button>();
+        let text = text::measure_ascii(button.text(), button.font_size());
+        assert!(button.measurement().width() >= text.width());
+        match button.frame_render() {
+            Some(frame::Node::Group(nodes)) => {
+                assert_eq!(nodes.len(), 2);
+                match nodes[1] {
+                    frame::Node::Text { rect, .. } => assert!(button.rect().contains_rect(rect)),
+                    _ => panic!("expected the text node after the background"),
+                }
+            }
+            _ => panic!("expected a group of background and text"),
+        }
+    }
+}
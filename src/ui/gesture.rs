@@ -0,0 +1,232 @@
+use crate::geom::FPoint;
+
+use std::time::{Duration, Instant};
+
+/// A gesture recognized from a sequence of pointer events
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    Tap(FPoint),
+    DoubleTap(FPoint),
+    LongPress(FPoint),
+}
+
+impl Gesture {
+    /// The position at which the gesture completed
+    pub fn position(&self) -> FPoint {
+        match *self {
+            Gesture::Tap(pos) => pos,
+            Gesture::DoubleTap(pos) => pos,
+            Gesture::LongPress(pos) => pos,
+        }
+    }
+
+    /// The same gesture at another position, e.g. mapped to a view local coordinates
+    pub fn with_position(&self, pos: FPoint) -> Gesture {
+        match *self {
+            Gesture::Tap(_) => Gesture::Tap(pos),
+            Gesture::DoubleTap(_) => Gesture::DoubleTap(pos),
+            Gesture::LongPress(_) => Gesture::LongPress(pos),
+        }
+    }
+}
+
+/// Thresholds used to recognize gestures
+#[derive(Clone, Copy, Debug)]
+pub struct Thresholds {
+    /// Maximum distance the pointer can travel between press and release of a tap
+    pub tap_radius: f32,
+    /// Maximum duration between press and release of a tap
+    pub tap_time: Duration,
+    /// Maximum duration between two taps making a double tap
+    pub double_tap_time: Duration,
+    /// Minimum duration between press and release of a long press
+    pub long_press_time: Duration,
+}
+
+impl Default for Thresholds {
+    fn default() -> Thresholds {
+        Thresholds {
+            tap_radius: 8f32,
+            tap_time: Duration::from_millis(300),
+            double_tap_time: Duration::from_millis(400),
+            long_press_time: Duration::from_millis(600),
+        }
+    }
+}
+
+/// Recognize gestures by tracking timing and position of press and release events
+#[derive(Debug, Default)]
+pub struct Recognizer {
+    thresholds: Thresholds,
+    press: Option<(FPoint, Instant)>,
+    last_tap: Option<(FPoint, Instant)>,
+}
+
+impl Recognizer {
+    pub fn new(thresholds: Thresholds) -> Recognizer {
+        Recognizer {
+            thresholds,
+            press: None,
+            last_tap: None,
+        }
+    }
+
+    pub fn thresholds(&self) -> Thresholds {
+        self.thresholds
+    }
+
+    pub fn set_thresholds(&mut self, thresholds: Thresholds) {
+        self.thresholds = thresholds;
+    }
+
+    /// Feed a press event
+    pub fn press(&mut self, pos: FPoint, time: Instant) {
+        self.press = Some((pos, time));
+    }
+
+    /// Feed a move event. A press that moves out of the tap radius is cancelled.
+    pub fn moved(&mut self, pos: FPoint) {
+        if let Some((press_pos, _)) = self.press {
+            if !self.within_radius(press_pos, pos) {
+                self.press = None;
+                self.last_tap = None;
+            }
+        }
+    }
+
+    /// Feed a release event and get the recognized gesture if any
+    pub fn release(&mut self, pos: FPoint, time: Instant) -> Option<Gesture> {
+        let (press_pos, press_time) = self.press.take()?;
+        if !self.within_radius(press_pos, pos) {
+            self.last_tap = None;
+            return None;
+        }
+        let held = time.duration_since(press_time);
+        if held >= self.thresholds.long_press_time {
+            self.last_tap = None;
+            Some(Gesture::LongPress(pos))
+        } else if held <= self.thresholds.tap_time {
+            match self.last_tap.take() {
+                Some((tap_pos, tap_time))
+                    if time.duration_since(tap_time) <= self.thresholds.double_tap_time
+                        && self.within_radius(tap_pos, pos) =>
+                {
+                    Some(Gesture::DoubleTap(pos))
+                }
+                _ => {
+                    self.last_tap = Some((pos, time));
+                    Some(Gesture::Tap(pos))
+                }
+            }
+        } else {
+            self.last_tap = None;
+            None
+        }
+    }
+
+    fn within_radius(&self, p1: FPoint, p2: FPoint) -> bool {
+        let (dx, dy) = (p2.0 - p1.0, p2.1 - p1.1);
+        dx * dx + dy * dy <= self.thresholds.tap_radius * self.thresholds.tap_radius
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::Point;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn tap() {
+        let mut rec = Recognizer::default();
+        let t0 = Instant::now();
+        rec.press(Point(10f32, 10f32), t0);
+        rec.moved(Point(13f32, 14f32));
+        let gesture = rec.release(Point(13f32, 14f32), t0 + ms(100));
+        assert_eq!(gesture, Some(Gesture::Tap(Point(13f32, 14f32))));
+    }
+
+    #[test]
+    fn release_without_press() {
+        let mut rec = Recognizer::default();
+        assert_eq!(rec.release(Point(10f32, 10f32), Instant::now()), None);
+    }
+
+    #[test]
+    fn long_press() {
+        let mut rec = Recognizer::default();
+        let t0 = Instant::now();
+        rec.press(Point(10f32, 10f32), t0);
+        let gesture = rec.release(Point(10f32, 10f32), t0 + ms(600));
+        assert_eq!(gesture, Some(Gesture::LongPress(Point(10f32, 10f32))));
+    }
+
+    #[test]
+    fn between_tap_and_long_press() {
+        let mut rec = Recognizer::default();
+        let t0 = Instant::now();
+        rec.press(Point(10f32, 10f32), t0);
+        assert_eq!(rec.release(Point(10f32, 10f32), t0 + ms(450)), None);
+    }
+
+    #[test]
+    fn drag_cancels() {
+        let mut rec = Recognizer::default();
+        let t0 = Instant::now();
+        rec.press(Point(10f32, 10f32), t0);
+        rec.moved(Point(30f32, 10f32));
+        // coming back within the radius does not restore the press
+        rec.moved(Point(10f32, 10f32));
+        assert_eq!(rec.release(Point(10f32, 10f32), t0 + ms(100)), None);
+    }
+
+    #[test]
+    fn release_out_of_radius() {
+        let mut rec = Recognizer::default();
+        let t0 = Instant::now();
+        rec.press(Point(10f32, 10f32), t0);
+        assert_eq!(rec.release(Point(30f32, 10f32), t0 + ms(100)), None);
+    }
+
+    #[test]
+    fn double_tap() {
+        let mut rec = Recognizer::default();
+        let t0 = Instant::now();
+        let p = Point(10f32, 10f32);
+        rec.press(p, t0);
+        assert_eq!(rec.release(p, t0 + ms(50)), Some(Gesture::Tap(p)));
+        rec.press(p, t0 + ms(200));
+        assert_eq!(rec.release(p, t0 + ms(250)), Some(Gesture::DoubleTap(p)));
+        // a third tap starts over
+        rec.press(p, t0 + ms(300));
+        assert_eq!(rec.release(p, t0 + ms(350)), Some(Gesture::Tap(p)));
+    }
+
+    #[test]
+    fn double_tap_too_late() {
+        let mut rec = Recognizer::default();
+        let t0 = Instant::now();
+        let p = Point(10f32, 10f32);
+        rec.press(p, t0);
+        assert_eq!(rec.release(p, t0 + ms(50)), Some(Gesture::Tap(p)));
+        rec.press(p, t0 + ms(500));
+        assert_eq!(rec.release(p, t0 + ms(550)), Some(Gesture::Tap(p)));
+    }
+
+    #[test]
+    fn drag_cancels_double_tap() {
+        let mut rec = Recognizer::default();
+        let t0 = Instant::now();
+        let p = Point(10f32, 10f32);
+        rec.press(p, t0);
+        assert_eq!(rec.release(p, t0 + ms(50)), Some(Gesture::Tap(p)));
+        rec.press(p, t0 + ms(100));
+        rec.moved(Point(10f32, 40f32));
+        assert_eq!(rec.release(Point(10f32, 40f32), t0 + ms(150)), None);
+        rec.press(p, t0 + ms(200));
+        assert_eq!(rec.release(p, t0 + ms(250)), Some(Gesture::Tap(p)));
+    }
+}
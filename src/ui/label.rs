@@ -1,14 +1,21 @@
-use crate::{Color, Paint};
+use crate::{ui, Color, Paint};
 use crate::color;
-use crate::geom::FRect;
+use crate::geom::{FRect, FSize, Size};
 use crate::render::frame;
-use crate::ui::view::{self, HasRect, MeasureSpec, View};
+use crate::ui::layout::measure_text;
+use crate::ui::view::{self, Base, HasPadding, HasRect, MeasureSpec, View};
 
-/// A view that can display text or image
+const DEFAULT_FONT_SIZE: f32 = 14f32;
+
+/// A view that can display text or image.
+/// A label without text is rendered as a rect filled with its color.
 #[derive(Debug)]
 pub struct Label {
     common: view::Common,
     color: Color,
+    text: String,
+    font_size: f32,
+    min_size: FSize,
 }
 
 impl Label {
@@ -16,37 +23,106 @@ impl Label {
         Label {
             common: view::Common::default(),
             color,
+            text: String::new(),
+            font_size: DEFAULT_FONT_SIZE,
+            min_size: Size(0f32, 0f32),
         }
     }
-}
 
-impl Default for Label {
-    fn default() -> Label {
+    pub fn new_with_text(text: String, color: Color) -> Label {
         Label {
             common: view::Common::default(),
-            color: Color::from(color::CssName::Black),
+            color,
+            text,
+            font_size: DEFAULT_FONT_SIZE,
+            min_size: Size(0f32, 0f32),
         }
     }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+        self.common.invalidate(ui::Dirty::FRAME);
+    }
+
+    /// The smallest size measured when not constrained by the parent
+    pub fn min_size(&self) -> FSize {
+        self.min_size
+    }
+
+    pub fn set_min_size(&mut self, min_size: FSize) {
+        self.min_size = min_size;
+        self.common.invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+
+    pub fn font_size(&self) -> f32 {
+        self.font_size
+    }
+
+    pub fn set_font_size(&mut self, font_size: f32) {
+        self.font_size = font_size;
+        self.common.invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+        self.common.invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+}
+
+impl Default for Label {
+    fn default() -> Label {
+        Label::new(Color::from(color::CssName::Black))
+    }
 }
 
 impl View for Label {}
 
 impl view::Measure for Label {
-    fn measure(&mut self, _specs: [MeasureSpec; 2]) {}
+    fn measure(&mut self, specs: [MeasureSpec; 2]) {
+        let size = measure_text(&self.text, self.font_size, self.padding(), self.min_size, specs);
+        self.set_constrained_measurement(size, specs);
+    }
 }
 
 impl view::Layout for Label {
-    fn layout(&mut self, _rect: FRect) {}
+    fn layout(&mut self, rect: FRect) {
+        self.common.rect = rect;
+    }
 }
 
+impl view::GestureHandler for Label {}
+
+impl view::PointerHandler for Label {}
+
+impl view::KeyHandler for Label {}
+
 impl view::FrameRender for Label {
     fn frame_render(&self) -> Option<frame::Node> {
-        Some(frame::Node::Rect {
-            rect: self.rect(),
-            paint: Paint::Solid(self.color),
-            radius: 0f32,
-            border: None,
-        })
+        let node = if !self.text.is_empty() {
+            // the text is drawn at the top-left corner of the padded rect
+            frame::Node::Text {
+                rect: self.rect() - self.padding(),
+                text: self.text.clone(),
+                font_size: self.font_size,
+                color: self.color,
+            }
+        } else {
+            frame::Node::Rect {
+                rect: self.rect(),
+                paint: Paint::Solid(self.color),
+                radius: 0f32.into(),
+                border: None,
+            }
+        };
+        Some(node)
     }
 }
 
@@ -61,3 +137,31 @@ impl view::Base for Label {
         &mut self.common
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::Margins;
+    use crate::ui::view::FrameRender;
+
+    #[test]
+    fn text_node_in_padded_rect() {
+        let mut label = Label::new_with_text("Hi".to_string(), Color::from(color::CssName::Red));
+        label.set_font_size(10f32);
+        label.set_padding(Margins(1f32, 2f32, 3f32, 4f32));
+        label.common.rect = FRect::new(10f32, 20f32, 30f32, 40f32);
+        match label.frame_render() {
+            Some(frame::Node::Text {
+                rect,
+                text,
+                font_size,
+                ..
+            }) => {
+                assert_eq!(rect, FRect::new(11f32, 22f32, 26f32, 34f32));
+                assert_eq!(text, "Hi");
+                assert_eq!(font_size, 10f32);
+            }
+            _ => panic!("expected a text node"),
+        }
+    }
+}
@@ -1,12 +1,13 @@
-use crate::geom::{FMargins, FRect, Margins, Size};
-use crate::render::frame;
+use crate::geom::{FMargins, FRect, FSize, Margins, Size};
+use crate::render::{frame, text};
 use crate::ui::view::Base;
 use crate::ui::view::Children;
-use crate::ui::view::{self, HasMargins, HasPadding, MeasureSpec};
-use crate::ui::View;
+use crate::ui::view::{self, HasPadding, MeasureSpec};
+use crate::ui::{self, Node, View};
 
 use std::cell::Cell;
 use std::ops::Add;
+use std::rc::Rc;
 
 #[derive(Copy, Clone, Debug)]
 pub enum Orientation {
@@ -129,15 +130,31 @@ bitflags! {
     }
 }
 
+impl AxisGravity {
+    /// Place an item of extent `size` within `space` according to the gravity.
+    /// Returns the offset and the extent of the placed item.
+    pub fn place(self, space: f32, size: f32) -> (f32, f32) {
+        if self.contains(AxisGravity::FILL) {
+            (0f32, space)
+        } else if self.contains(AxisGravity::CENTER) {
+            ((space - size) / 2f32, size)
+        } else if self.contains(AxisGravity::PULL_AFTER) {
+            (space - size, size)
+        } else {
+            (0f32, size)
+        }
+    }
+}
+
 impl Gravity {
     /// Get the horizontal gravity
-    fn horizontal(self) -> AxisGravity {
+    pub fn horizontal(self) -> AxisGravity {
         AxisGravity {
             bits: (self.bits >> grav::SHIFT_HOR) & grav::MASK,
         }
     }
     /// Get the vertical gravity
-    fn vertical(self) -> AxisGravity {
+    pub fn vertical(self) -> AxisGravity {
         AxisGravity {
             bits: (self.bits >> grav::SHIFT_VER) & grav::MASK,
         }
@@ -165,12 +182,81 @@ pub enum LayoutSize {
     WrapContent,
 }
 
+/// Parameters of a view that are interpreted by its parent layout
+#[derive(Clone, Copy, Debug)]
+pub struct Params {
+    pub width: LayoutSize,
+    pub height: LayoutSize,
+    /// Share of the excess space given to the view along the orientation of a
+    /// `LinearLayout`. Zero means the view does not take part in the distribution.
+    pub weight: f32,
+    /// Placement of the view within a `StackLayout`
+    pub gravity: Gravity,
+}
+
+impl Params {
+    /// Get the layout size along the specified orientation
+    fn size_along(self, orientation: Orientation) -> LayoutSize {
+        match orientation {
+            Orientation::Horizontal => self.width,
+            Orientation::Vertical => self.height,
+        }
+    }
+}
+
+impl Default for Params {
+    fn default() -> Params {
+        Params {
+            width: LayoutSize::WrapContent,
+            height: LayoutSize::WrapContent,
+            weight: 0f32,
+            gravity: Gravity::default(),
+        }
+    }
+}
+
+/// Distribution of the leftover main-axis space of a `LinearLayout` between its children
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Distribution {
+    /// Children are packed at the start
+    Start,
+    /// Children are packed at the end
+    End,
+    /// Children are packed at the center
+    Center,
+    /// The space goes between children, none before the first nor after the last
+    SpaceBetween,
+    /// Each child gets the same space on both of its sides, so the space between
+    /// children is twice the space before the first and after the last
+    SpaceAround,
+    /// The space between children, before the first and after the last is the same
+    SpaceEvenly,
+}
+
+impl Distribution {
+    /// Get the offset of the first child and the additional gap between children,
+    /// to distribute `leftover` space among `count` children
+    fn offset_and_gap(self, leftover: f32, count: usize) -> (f32, f32) {
+        let n = count as f32;
+        match self {
+            Distribution::Start => (0f32, 0f32),
+            Distribution::End => (leftover, 0f32),
+            Distribution::Center => (leftover / 2f32, 0f32),
+            Distribution::SpaceBetween if count > 1 => (0f32, leftover / (n - 1f32)),
+            Distribution::SpaceBetween => (0f32, 0f32),
+            Distribution::SpaceAround => (leftover / (2f32 * n), leftover / n),
+            Distribution::SpaceEvenly => (leftover / (n + 1f32), leftover / (n + 1f32)),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct LinearLayout {
     common: view::Common,
     orientation: Orientation,
     total_length: Cell<f32>,
     gravity: Gravity,
+    distribution: Option<Distribution>,
     spacing: f32,
 }
 
@@ -183,6 +269,7 @@ impl LinearLayout {
             orientation,
             total_length: Cell::new(0f32),
             gravity: Default::default(),
+            distribution: None,
             spacing: 0f32,
         }
     }
@@ -205,14 +292,30 @@ impl LinearLayout {
 
     pub fn set_gravity(&mut self, gravity: Gravity) {
         self.gravity = gravity;
+        self.common.invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+
+    pub fn distribution(&self) -> Option<Distribution> {
+        self.distribution
+    }
+
+    /// Set how the leftover main-axis space is distributed between children.
+    /// When set, it overrides the main-axis gravity. When `None`, the main-axis
+    /// gravity places the children.
+    pub fn set_distribution(&mut self, distribution: Option<Distribution>) {
+        self.distribution = distribution;
+        self.common.invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
     }
 
     pub fn spacing(&self) -> f32 {
         self.spacing
     }
 
+    /// Set the space between two consecutive children, none before the first
+    /// nor after the last
     pub fn set_spacing(&mut self, spacing: f32) {
         self.spacing = spacing;
+        self.common.invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
     }
 }
 
@@ -223,17 +326,18 @@ impl LinearLayout {
         parent_specs: [MeasureSpec; 2],
         used_space: [f32; 2],
     ) {
+        let params = view.layout_params();
         let ws = child_measure_spec(
             parent_specs[0],
             padding.horizontal() + used_space[0],
-            LayoutSize::WrapContent,
+            params.width,
         );
         let hs = child_measure_spec(
             parent_specs[1],
             padding.vertical() + used_space[1],
-            LayoutSize::WrapContent,
+            params.height,
         );
-        view.measure([ws, hs]);
+        measure_if_dirty(view, [ws, hs]);
     }
 }
 
@@ -243,96 +347,137 @@ impl view::Measure for LinearLayout {
     fn measure(&mut self, specs: [MeasureSpec; 2]) {
         let mut total = [0f32; 2];
         let mut largest_ortho = 0f32;
-        //let mut total_weight = 0f32;
-        let ind = self.orientation as usize;
-        let ind_ortho = self.orientation.ortho() as usize;
+        let mut total_weight = 0f32;
+        let mut count = 0;
+        let orientation = self.orientation;
+        let ortho = orientation.ortho();
+        let ind = orientation as usize;
+        let ind_ortho = ortho as usize;
 
         let padding = self.padding();
 
-        for node in self.children() {
+        for node in self.children().skip_gone() {
             let mut view = node.view_mut();
             Self::measure_child(padding, &mut *view, specs, total);
             let m: [f32; 2] = From::from(view.measurement());
-            total[ind] += m[ind];
-            largest_ortho =
-                largest_ortho.max(m[ind_ortho] + view.margins().along(self.orientation.ortho()));
-            // TODO weight
+            total[ind] += m[ind] + view.margins().along(orientation);
+            largest_ortho = largest_ortho.max(m[ind_ortho] + view.margins().along(ortho));
+            total_weight += view.layout_params().weight;
+            count += 1;
+        }
+        if count > 1 {
+            total[ind] += self.spacing * (count - 1) as f32;
+        }
+        total[ind] += padding.along(orientation);
+
+        let final_size = resolve_size(total[ind], specs[ind]);
+        let remain_excess = final_size - total[ind];
+
+        if total_weight > 0f32 && remain_excess > 0f32 {
+            // distribute remain_excess according weight
+            // weighted children are measured again with their share of the excess
+            largest_ortho = 0f32;
+            for node in self.children().skip_gone() {
+                let mut view = node.view_mut();
+                let params = view.layout_params();
+                if params.weight > 0f32 {
+                    let share = remain_excess * params.weight / total_weight;
+                    let mut child_specs = [MeasureSpec::Unspecified; 2];
+                    child_specs[ind] =
+                        MeasureSpec::Exactly(view.measurement().along(orientation) + share);
+                    child_specs[ind_ortho] = child_measure_spec(
+                        specs[ind_ortho],
+                        padding.along(ortho),
+                        params.size_along(ortho),
+                    );
+                    measure_if_dirty(&mut *view, child_specs);
+                    total[ind] += share;
+                }
+                largest_ortho = largest_ortho
+                    .max(view.measurement().along(ortho) + view.margins().along(ortho));
+            }
         }
-        total[ind] += self.padding().along(self.orientation);
-
-        let mut too_small = [false, false];
-        //let mut final_size = resolve_size(total[ind], specs[ind], &mut too_small[ind]);
-        //let mut remain_excess = final_size - total[ind];
-
-        // TODO distribute remain_excess according weight
 
-        largest_ortho += self.padding().along(self.orientation.ortho());
+        largest_ortho += padding.along(ortho);
         total[ind_ortho] = largest_ortho;
-        self.set_measurement(Size(
-            resolve_size(total[0], specs[0], &mut too_small[0]),
-            resolve_size(total[1], specs[1], &mut too_small[1]),
-        ));
-        if too_small[0] || too_small[1] {
-            println!("layout too small!");
-        }
+        self.set_constrained_measurement(
+            Size(
+                resolve_size(total[0], specs[0]),
+                resolve_size(total[1], specs[1]),
+            ),
+            specs,
+        );
         self.total_length.set(total[ind]);
     }
 }
 
 impl view::Layout for LinearLayout {
     fn layout(&mut self, rect: FRect) {
+        self.common.rect = rect;
         let orientation = self.orientation;
         let ortho = self.orientation.ortho();
         let padding = self.padding();
-        let margins = self.margins();
-
-        let mut child_before = match self.gravity.along(self.orientation) {
-            AxisGravity::PULL_AFTER => {
-                padding.along(self.orientation) + rect.size().along(self.orientation)
-                    - self.total_length.get()
+        let size = rect.size();
+
+        let leftover = size.along(orientation) - self.total_length.get();
+        let (mut child_before, gap) = match self.distribution {
+            Some(distribution) if leftover > 0f32 => {
+                let count = self.children().skip_gone().count();
+                let (offset, gap) = distribution.offset_and_gap(leftover, count);
+                (padding.along_before(orientation) + offset, gap)
             }
-            AxisGravity::CENTER => {
-                padding.along(self.orientation)
-                    + (rect.size().along(self.orientation) - self.total_length.get()) / 2f32
-            }
-            _ => padding.along(self.orientation),
+            Some(_) => (padding.along_before(orientation), 0f32),
+            None => match self.gravity.along(orientation) {
+                AxisGravity::PULL_AFTER => (padding.along_before(orientation) + leftover, 0f32),
+                AxisGravity::CENTER => (padding.along_before(orientation) + leftover / 2f32, 0f32),
+                _ => (padding.along_before(orientation), 0f32),
+            },
         };
 
-        let child_ortho_after = rect.size().along(ortho) - padding.along_after(ortho);
+        let child_ortho_after = size.along(ortho) - padding.along_after(ortho);
         let child_ortho_space = child_ortho_after - padding.along_before(ortho);
         let mut first = true;
 
-        for node in self.children() {
-            // TODO: child margins
+        for node in self.children().skip_gone() {
             let mut view = node.view_mut();
             let mes = view.measurement();
+            let child_margins = view.margins();
             let child_ortho_before = match self.gravity.along(ortho) {
                 AxisGravity::PULL_AFTER => {
-                    child_ortho_after - mes.along(ortho) // - child_margins.along(ortho)
+                    child_ortho_after - mes.along(ortho) - child_margins.along_after(ortho)
                 }
                 AxisGravity::CENTER => {
                     padding.along_before(ortho)
-                        + (child_ortho_space - mes.along(ortho)/* - child_margins.along(ortho) */)
+                        + child_margins.along_before(ortho)
+                        + (child_ortho_space - mes.along(ortho) - child_margins.along(ortho))
                             / 2f32
                 }
-                _ => padding.along_before(ortho),
+                _ => padding.along_before(ortho) + child_margins.along_before(ortho),
             };
 
-            if first {
-                child_before += self.spacing;
-                first = false;
+            if !first {
+                child_before += self.spacing + gap;
             }
+            first = false;
+            child_before += child_margins.along_before(orientation);
+
             let mut point = [0f32; 2];
-            point[orientation as usize] = child_before + margins.along_before(orientation);
-            point[ortho as usize] = child_ortho_before + margins.along_before(ortho);
+            point[orientation as usize] = child_before;
+            point[ortho as usize] = child_ortho_before;
 
-            view.layout(FRect::new_s(point[0], point[1], mes));
+            layout_if_dirty(&mut *view, FRect::new_s(point[0], point[1], mes));
 
-            child_before += mes.along(self.orientation);
+            child_before += mes.along(orientation) + child_margins.along_after(orientation);
         }
     }
 }
 
+impl view::GestureHandler for LinearLayout {}
+
+impl view::PointerHandler for LinearLayout {}
+
+impl view::KeyHandler for LinearLayout {}
+
 impl view::FrameRender for LinearLayout {
     fn frame_render(&self) -> Option<frame::Node> {
         None
@@ -351,48 +496,371 @@ impl view::Base for LinearLayout {
     }
 }
 
+/// Measure `view` with `specs`, unless its node is clean for layout and the view
+/// was last measured with the same specs. Containers measure their children with it,
+/// so that the subtrees that did not change are not measured again.
+pub fn measure_if_dirty(view: &mut dyn View, specs: [MeasureSpec; 2]) {
+    let node = view.node();
+    if node.dirty().contains(ui::Dirty::LAYOUT) || node.measured_specs() != Some(specs) {
+        view.measure(specs);
+        node.set_measured_specs(specs);
+        // a new measurement needs a new layout, even within the same rect
+        node.mark_dirty_self(ui::Dirty::LAYOUT);
+    }
+}
+
+/// Lay `view` out in `rect`, unless its node is clean for layout and the view
+/// already has this rect. The node is clean for layout afterwards.
+pub fn layout_if_dirty(view: &mut dyn View, rect: FRect) {
+    let node = view.node();
+    if node.dirty().contains(ui::Dirty::LAYOUT) || view.rect() != rect {
+        view.layout(rect);
+    }
+    node.clear_dirty(ui::Dirty::LAYOUT);
+}
+
 /// Provide the measure spec to be given to a child
+///
 /// # Arguments
-///     * `parent_spec`         - the measure spec of the parent
-///     * `removed`             - how much has been consumed so far from the parent space
-///     * `child_layout_size`   - the child size given in layout params
+/// * `parent_spec` - the measure spec of the parent
+/// * `removed` - how much has been consumed so far from the parent space
+/// * `child_layout_size` - the child size given in layout params
+///
+/// A `MatchParent` child of a parent measured with `Unspecified` (e.g. along the axis
+/// of a scroll view) has no parent size to match, and is measured as `WrapContent`.
+/// A parent measured with `AtLeast` has no upper bound: a `MatchParent` child gets
+/// the same lower bound and a `WrapContent` child is unconstrained.
 pub fn child_measure_spec(
     parent_spec: MeasureSpec,
     removed: f32,
     child_layout_size: LayoutSize,
 ) -> MeasureSpec {
-    match child_layout_size {
-        LayoutSize::Scalar(val) => MeasureSpec::Exactly(val),
-        _ => match parent_spec {
-            MeasureSpec::Exactly(size) => {
-                let size = 0f32.max(size - removed);
-                match child_layout_size {
-                    LayoutSize::WrapContent => MeasureSpec::AtMost(size),
-                    LayoutSize::MatchParent => MeasureSpec::Exactly(size),
-                    _ => panic!(),
-                }
-            }
-            MeasureSpec::AtMost(size) => MeasureSpec::AtMost(0f32.max(size - removed)),
-            MeasureSpec::Unspecified => MeasureSpec::Unspecified,
-        },
+    match (parent_spec, child_layout_size) {
+        (_, LayoutSize::Scalar(val)) => MeasureSpec::Exactly(val),
+        (MeasureSpec::Exactly(size), LayoutSize::MatchParent) => {
+            MeasureSpec::Exactly(0f32.max(size - removed))
+        }
+        (MeasureSpec::Exactly(size), LayoutSize::WrapContent)
+        | (MeasureSpec::AtMost(size), _) => MeasureSpec::AtMost(0f32.max(size - removed)),
+        (MeasureSpec::AtLeast(size), LayoutSize::MatchParent) => {
+            MeasureSpec::AtLeast(0f32.max(size - removed))
+        }
+        (MeasureSpec::AtLeast(_), LayoutSize::WrapContent)
+        | (MeasureSpec::Unspecified, LayoutSize::MatchParent)
+        | (MeasureSpec::Unspecified, LayoutSize::WrapContent) => MeasureSpec::Unspecified,
     }
 }
 
 /// Reconciliate a measure spec and children dimensions.
 /// This will give the final dimension to be shared amoung the children.
-/// `too_small` will be set to true if size is bigger than the spec size, false otherwise.
-pub fn resolve_size(size: f32, spec: MeasureSpec, too_small: &mut bool) -> f32 {
-    *too_small = false;
+/// An `AtMost` spec clamps the size to its bound, an `AtLeast` spec grows it to its bound.
+pub fn resolve_size(size: f32, spec: MeasureSpec) -> f32 {
     match spec {
-        MeasureSpec::AtMost(at_most) => {
-            if size > at_most {
-                *too_small = true;
-                at_most
-            } else {
-                size
-            }
-        }
+        MeasureSpec::AtLeast(at_least) => size.max(at_least),
+        MeasureSpec::AtMost(at_most) => size.min(at_most),
         MeasureSpec::Exactly(exactly) => exactly,
         MeasureSpec::Unspecified => size,
     }
 }
+
+/// Measure the children, and size to the largest of them, margins included, plus `padding`
+pub(crate) fn measure_largest_child<I>(
+    children: I,
+    padding: FMargins,
+    specs: [MeasureSpec; 2],
+) -> FSize
+where
+    I: Iterator<Item = Rc<Node>>,
+{
+    let mut largest = [0f32; 2];
+    for node in children {
+        let mut view = node.view_mut();
+        let params = view.layout_params();
+        let margins = view.margins();
+        let ws = child_measure_spec(
+            specs[0],
+            padding.horizontal() + margins.horizontal(),
+            params.width,
+        );
+        let hs = child_measure_spec(
+            specs[1],
+            padding.vertical() + margins.vertical(),
+            params.height,
+        );
+        measure_if_dirty(&mut *view, [ws, hs]);
+        let mes = view.measurement();
+        largest[0] = largest[0].max(mes.width() + margins.horizontal());
+        largest[1] = largest[1].max(mes.height() + margins.vertical());
+    }
+    Size(
+        resolve_size(largest[0] + padding.horizontal(), specs[0]),
+        resolve_size(largest[1] + padding.vertical(), specs[1]),
+    )
+}
+
+/// Measure a text of `font_size` surrounded by `padding`, and no smaller than `min_size`
+pub(crate) fn measure_text(
+    text: &str,
+    font_size: f32,
+    padding: FMargins,
+    min_size: FSize,
+    specs: [MeasureSpec; 2],
+) -> FSize {
+    let text = text::measure_ascii(text, font_size);
+    let width = (text.width() + padding.horizontal()).max(min_size.width());
+    let height = (text.height() + padding.vertical()).max(min_size.height());
+    Size(resolve_size(width, specs[0]), resolve_size(height, specs[1]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::tests::{set_up, Probe};
+    use crate::ui::{Label, Node, UserInterface};
+    use std::rc::Rc;
+
+    #[test]
+    fn mark_dirty_ancestors_not_siblings() {
+        let ui = UserInterface::new();
+        let root = Node::new(LinearLayout::new_vertical(), ui.clone(), None);
+        let a = Node::new(LinearLayout::new_horizontal(), ui.clone(), None);
+        let a1 = Node::new(Label::default(), ui.clone(), None);
+        let a2 = Node::new(Label::default(), ui.clone(), None);
+        let b = Node::new(Label::default(), ui.clone(), None);
+        a.add_child(&a1, None);
+        a.add_child(&a2, None);
+        root.add_child(&a, None);
+        root.add_child(&b, None);
+        set_up(&ui, &root, (100f64, 100f64));
+        for n in &[&root, &a, &a1, &a2, &b] {
+            assert!(!n.dirty().contains(ui::Dirty::LAYOUT));
+        }
+
+        a1.mark_dirty(ui::Dirty::LAYOUT);
+        assert!(a1.dirty().contains(ui::Dirty::LAYOUT));
+        assert!(a.dirty().contains(ui::Dirty::LAYOUT));
+        assert!(root.dirty().contains(ui::Dirty::LAYOUT));
+        assert!(!a2.dirty().contains(ui::Dirty::LAYOUT));
+        assert!(!b.dirty().contains(ui::Dirty::LAYOUT));
+        assert!(ui.dirty(ui::Dirty::LAYOUT));
+    }
+
+    #[test]
+    fn clean_subtree_is_skipped() {
+        let ui = UserInterface::new();
+        let root = Node::new(LinearLayout::new_vertical(), ui.clone(), None);
+        let probe = Probe::new(Size(10f32, 10f32));
+        let measures = probe.measures();
+        let probe = Node::new(probe, ui.clone(), None);
+        let label = Node::new(Label::default(), ui.clone(), None);
+        root.add_child(&probe, None);
+        root.add_child(&label, None);
+        set_up(&ui, &root, (100f64, 100f64));
+        assert_eq!(measures.get(), 1);
+
+        // the sibling changes, the probe is measured with the same specs
+        label.view_as_mut::<Label>().set_min_size(Size(20f32, 20f32));
+        ui.layout();
+        assert_eq!(label.view().measurement(), Size(20f32, 20f32));
+        assert_eq!(measures.get(), 1);
+
+        // the window width changes the specs of the probe
+        set_up(&ui, &root, (50f64, 100f64));
+        assert_eq!(measures.get(), 2);
+        assert!(!probe.dirty().contains(ui::Dirty::LAYOUT));
+    }
+
+    /// Labels of the given min sizes in a layout of the given orientation
+    fn linear(orientation: Orientation, sizes: &[(f32, f32)]) -> (Rc<UserInterface>, Rc<Node>) {
+        let ui = UserInterface::new();
+        let root = Node::new(LinearLayout::new(orientation), ui.clone(), None);
+        for &(w, h) in sizes {
+            let mut label = Label::default();
+            label.set_min_size(Size(w, h));
+            root.add_child(&Node::new(label, ui.clone(), None), None);
+        }
+        (ui, root)
+    }
+
+    fn child_rects(node: &Node) -> Vec<FRect> {
+        let layout = node.view_as::<LinearLayout>();
+        layout.children().map(|c| c.view().rect()).collect()
+    }
+
+    #[test]
+    fn weighted_split() {
+        let (_ui, root) = linear(Orientation::Horizontal, &[(0f32, 10f32), (0f32, 10f32)]);
+        for (node, &weight) in root.view_as::<LinearLayout>().children().zip(&[1f32, 2f32]) {
+            node.view_as_mut::<Label>().set_layout_params(Params {
+                weight,
+                ..Params::default()
+            });
+        }
+        let specs = [MeasureSpec::Exactly(300f32), MeasureSpec::AtMost(100f32)];
+        measure_if_dirty(&mut *root.view_mut(), specs);
+        layout_if_dirty(&mut *root.view_mut(), FRect::new(0f32, 0f32, 300f32, 10f32));
+        let rects = child_rects(&root);
+        assert_eq!(rects[0], FRect::new(0f32, 0f32, 100f32, 10f32));
+        assert_eq!(rects[1], FRect::new(100f32, 0f32, 200f32, 10f32));
+    }
+
+    /// The x of three 10 px wide children distributed in a 100 px wide layout
+    fn distributed_xs(distribution: Distribution) -> Vec<f32> {
+        let (_ui, root) = linear(Orientation::Horizontal, &[(10f32, 10f32); 3]);
+        root.view_as_mut::<LinearLayout>().set_distribution(Some(distribution));
+        let specs = [MeasureSpec::Exactly(100f32), MeasureSpec::AtMost(100f32)];
+        measure_if_dirty(&mut *root.view_mut(), specs);
+        layout_if_dirty(&mut *root.view_mut(), FRect::new(0f32, 0f32, 100f32, 10f32));
+        child_rects(&root).iter().map(|r| r.x).collect()
+    }
+
+    fn assert_xs(xs: Vec<f32>, expected: [f32; 3]) {
+        for (x, e) in xs.iter().zip(expected.iter()) {
+            assert!((x - e).abs() < 1e-4, "{:?} != {:?}", xs, expected);
+        }
+    }
+
+    #[test]
+    fn distribution_start() {
+        assert_eq!(Distribution::Start.offset_and_gap(70f32, 3), (0f32, 0f32));
+        assert_xs(distributed_xs(Distribution::Start), [0f32, 10f32, 20f32]);
+    }
+
+    #[test]
+    fn distribution_end() {
+        assert_eq!(Distribution::End.offset_and_gap(70f32, 3), (70f32, 0f32));
+        assert_xs(distributed_xs(Distribution::End), [70f32, 80f32, 90f32]);
+    }
+
+    #[test]
+    fn distribution_center() {
+        assert_eq!(Distribution::Center.offset_and_gap(70f32, 3), (35f32, 0f32));
+        assert_xs(distributed_xs(Distribution::Center), [35f32, 45f32, 55f32]);
+    }
+
+    #[test]
+    fn distribution_space_between() {
+        assert_eq!(Distribution::SpaceBetween.offset_and_gap(70f32, 3), (0f32, 35f32));
+        // a single child stays at the start
+        assert_eq!(Distribution::SpaceBetween.offset_and_gap(70f32, 1), (0f32, 0f32));
+        assert_xs(distributed_xs(Distribution::SpaceBetween), [0f32, 45f32, 90f32]);
+    }
+
+    #[test]
+    fn distribution_space_around() {
+        let (offset, gap) = Distribution::SpaceAround.offset_and_gap(60f32, 3);
+        assert_eq!((offset, gap), (10f32, 20f32));
+        assert_xs(
+            distributed_xs(Distribution::SpaceAround),
+            [70f32 / 6f32, 45f32, 90f32 - 70f32 / 6f32],
+        );
+    }
+
+    #[test]
+    fn distribution_space_evenly() {
+        assert_eq!(Distribution::SpaceEvenly.offset_and_gap(80f32, 3), (20f32, 20f32));
+        assert_xs(distributed_xs(Distribution::SpaceEvenly), [17.5f32, 45f32, 72.5f32]);
+    }
+
+    /// Two 10x10 labels with margins left 1, top 2, right 3, bottom 4 and a spacing of 5
+    fn spaced_row() -> (Rc<UserInterface>, Rc<Node>) {
+        let (ui, root) = linear(Orientation::Horizontal, &[(10f32, 10f32), (10f32, 10f32)]);
+        for node in root.view_as::<LinearLayout>().children() {
+            node.view_as_mut::<Label>().set_margins(Margins(1f32, 2f32, 3f32, 4f32));
+        }
+        root.view_as_mut::<LinearLayout>().set_spacing(5f32);
+        (ui, root)
+    }
+
+    #[test]
+    fn spacing_and_margins() {
+        let (ui, root) = spaced_row();
+        set_up(&ui, &root, (100f64, 100f64));
+        // 1 + 10 + 3, spacing, 1 + 10 + 3
+        assert_eq!(root.view().measurement(), Size(33f32, 16f32));
+        let rects = child_rects(&root);
+        assert_eq!(rects[0], FRect::new(1f32, 2f32, 10f32, 10f32));
+        assert_eq!(rects[1], FRect::new(20f32, 2f32, 10f32, 10f32));
+
+        root.view_as_mut::<LinearLayout>().set_spacing(0f32);
+        ui.layout();
+        assert_eq!(child_rects(&root)[1], FRect::new(15f32, 2f32, 10f32, 10f32));
+    }
+
+    #[test]
+    fn pull_after() {
+        let (ui, root) = spaced_row();
+        root.view_as_mut::<LinearLayout>().set_gravity(Gravity::BOTTOM_RIGHT);
+        ui.set_gravity(Gravity::FILL);
+        set_up(&ui, &root, (100f64, 50f64));
+        // the children are pushed to the right end, after the last right margin
+        let rects = child_rects(&root);
+        assert_eq!(rects[0], FRect::new(68f32, 36f32, 10f32, 10f32));
+        assert_eq!(rects[1], FRect::new(87f32, 36f32, 10f32, 10f32));
+    }
+
+    #[test]
+    fn at_least_spec() {
+        use self::MeasureSpec::*;
+        let spec = |parent: MeasureSpec, size: LayoutSize| child_measure_spec(parent, 10f32, size);
+        assert_eq!(spec(AtLeast(100f32), LayoutSize::MatchParent), AtLeast(90f32));
+        assert_eq!(spec(AtLeast(100f32), LayoutSize::WrapContent), Unspecified);
+        assert_eq!(spec(AtLeast(100f32), LayoutSize::Scalar(5f32)), Exactly(5f32));
+        assert_eq!(spec(AtLeast(5f32), LayoutSize::MatchParent), AtLeast(0f32));
+
+        assert_eq!(resolve_size(50f32, AtLeast(80f32)), 80f32);
+        assert_eq!(resolve_size(120f32, AtLeast(80f32)), 120f32);
+        assert_eq!(resolve_size(120f32, AtMost(80f32)), 80f32);
+        assert_eq!(resolve_size(50f32, AtMost(80f32)), 50f32);
+    }
+
+    #[test]
+    fn at_least_propagation() {
+        let (_ui, root) = linear(Orientation::Vertical, &[(10f32, 10f32), (10f32, 10f32)]);
+        let first = root.first_child().unwrap();
+        first.view_as_mut::<Label>().set_layout_params(Params {
+            width: LayoutSize::MatchParent,
+            ..Params::default()
+        });
+        let specs = [MeasureSpec::AtLeast(60f32), MeasureSpec::AtLeast(50f32)];
+        measure_if_dirty(&mut *root.view_mut(), specs);
+        assert_eq!(root.view().measurement(), Size(60f32, 50f32));
+        assert_eq!(first.view().measurement(), Size(60f32, 10f32));
+        let last = root.last_child().unwrap();
+        assert_eq!(last.view().measurement(), Size(10f32, 10f32));
+    }
+
+    #[test]
+    fn match_parent_unspecified() {
+        assert_eq!(
+            child_measure_spec(MeasureSpec::Unspecified, 10f32, LayoutSize::MatchParent),
+            MeasureSpec::Unspecified
+        );
+        // along the axis of a scroll view, a MatchParent child wraps its content
+        let (_ui, root) = linear(Orientation::Vertical, &[(10f32, 20f32)]);
+        let child = root.first_child().unwrap();
+        child.view_as_mut::<Label>().set_layout_params(Params {
+            width: LayoutSize::MatchParent,
+            height: LayoutSize::MatchParent,
+            ..Params::default()
+        });
+        let specs = [MeasureSpec::Exactly(100f32), MeasureSpec::Unspecified];
+        measure_if_dirty(&mut *root.view_mut(), specs);
+        assert_eq!(child.view().measurement(), Size(100f32, 20f32));
+        assert_eq!(root.view().measurement(), Size(100f32, 20f32));
+    }
+
+    #[test]
+    fn aspect_ratio_in_square() {
+        let ui = UserInterface::new();
+        let mut label = Label::default();
+        label.set_min_size(Size(10f32, 10f32));
+        label.set_aspect_ratio(Some(16f32 / 9f32));
+        let node = Node::new(label, ui, None);
+        let specs = [MeasureSpec::AtMost(90f32), MeasureSpec::AtMost(90f32)];
+        measure_if_dirty(&mut *node.view_mut(), specs);
+        let mes = node.view().measurement();
+        assert_eq!(mes.width(), 90f32);
+        assert!((mes.height() - 50.625f32).abs() < 1e-4);
+    }
+}
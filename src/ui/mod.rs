@@ -1,26 +1,52 @@
-use crate::Color;
-use crate::geom::{FSize, FRect, IRect, Size};
+use crate::{Color, Transform};
+use crate::geom::{self, FPoint, FSize, FRect, IRect, Point, Size};
 use crate::render;
 use std::cell::{Cell, RefCell};
 use std::rc::Rc;
+use std::time::Instant;
 use winit::Window;
 
+pub mod anim;
+pub mod button;
+pub mod gesture;
 pub mod label;
 pub mod layout;
 pub mod node;
+pub mod picture;
+pub mod scroll;
+pub mod stack;
+pub mod transform;
 pub mod view;
+pub mod wrap;
 
+pub use self::button::Button;
 pub use self::label::Label;
 pub use self::layout::LinearLayout;
-pub use self::node::Node;
+pub use self::node::{Node, NodeBuilder};
+pub use self::picture::Picture;
+pub use self::scroll::ScrollView;
+pub use self::stack::StackLayout;
+pub use self::transform::TransformView;
 pub use self::view::View;
+pub use self::wrap::WrapLayout;
+
+/// Distance scrolled by a mouse wheel reporting deltas in lines
+const WHEEL_LINE_LENGTH: f32 = 20f32;
 
 #[derive(Debug)]
 pub struct UserInterface {
     root: RefCell<Option<Rc<Node>>>,
     size: Cell<FSize>,
+    scale_factor: Cell<f32>,
     clear_color: Cell<Option<Color>>,
     dirty: Cell<Dirty>,
+    gravity: Cell<layout::Gravity>,
+    disabled_hit_test: Cell<DisabledHitTest>,
+    cursor: Cell<FPoint>,
+    pressed: RefCell<Option<Rc<Node>>>,
+    hovered: RefCell<Option<Rc<Node>>>,
+    focused: RefCell<Option<Rc<Node>>>,
+    gestures: RefCell<gesture::Recognizer>,
 }
 
 impl UserInterface {
@@ -28,8 +54,16 @@ impl UserInterface {
         Rc::new(UserInterface {
             root: RefCell::new(None),
             size: Cell::new(Size(0f32, 0f32)),
+            scale_factor: Cell::new(1f32),
             clear_color: Cell::new(None),
             dirty: Cell::new(Dirty::all()),
+            gravity: Cell::new(layout::Gravity::default()),
+            disabled_hit_test: Cell::new(DisabledHitTest::SkipSubtree),
+            cursor: Cell::new(Point(0f32, 0f32)),
+            pressed: RefCell::new(None),
+            hovered: RefCell::new(None),
+            focused: RefCell::new(None),
+            gestures: RefCell::new(gesture::Recognizer::default()),
         })
     }
 
@@ -44,16 +78,116 @@ impl UserInterface {
         self.add_dirty(Dirty::LAYOUT | Dirty::STYLE | Dirty::FRAME);
     }
 
+    /// Describe the view tree for debugging, one node per line (see `Node::dump`)
+    pub fn dump_tree(&self) -> String {
+        self.root.borrow().as_ref().map_or(String::new(), |root| root.dump())
+    }
+
     /// Get the size of the user interface
     pub fn size(&self) -> FSize {
         self.size.get()
     }
 
+    /// Get the HiDPI factor of the window: ratio of physical pixels per logical unit.
+    /// Layout is done in logical units, which the frame maps to physical pixels.
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor.get()
+    }
+
+    /// Get the gravity of the root view within the window
+    pub fn gravity(&self) -> layout::Gravity {
+        self.gravity.get()
+    }
+
+    /// Set the gravity of the root view within the window.
+    /// The default places the root at its measured size in the top-left corner.
+    pub fn set_gravity(&self, gravity: layout::Gravity) {
+        self.gravity.set(gravity);
+        self.add_dirty(Dirty::LAYOUT | Dirty::FRAME);
+    }
+
     /// Checks whether all given dirty flags are set
     pub fn dirty(&self, flags: Dirty) -> bool {
         self.dirty.get().contains(flags)
     }
 
+    /// Get how hit-testing treats disabled views
+    pub fn disabled_hit_test(&self) -> DisabledHitTest {
+        self.disabled_hit_test.get()
+    }
+
+    /// Set how hit-testing treats disabled views
+    pub fn set_disabled_hit_test(&self, policy: DisabledHitTest) {
+        self.disabled_hit_test.set(policy);
+    }
+
+    /// Get the topmost node under the point `p`, given in window coordinates.
+    /// Children are tested before their parent, the last child first.
+    pub fn node_at(&self, p: FPoint) -> Option<Rc<Node>> {
+        let root = self.root.borrow().clone();
+        root.and_then(|root| self.node_at_in(&root, p))
+    }
+
+    /// Get the node that has the keyboard focus
+    pub fn focused(&self) -> Option<Rc<Node>> {
+        self.focused.borrow().clone()
+    }
+
+    /// Give the keyboard focus to `node`, or remove the focus if `None`.
+    /// The views losing and gaining the focus are notified.
+    pub fn set_focus(&self, node: Option<Rc<Node>>) {
+        let prev = self.focused.replace(node.clone());
+        match (&prev, &node) {
+            (Some(prev), Some(node)) if Rc::ptr_eq(prev, node) => return,
+            _ => {}
+        }
+        if let Some(prev) = prev {
+            prev.view_mut().on_focus(false);
+        }
+        if let Some(node) = node {
+            node.view_mut().on_focus(true);
+        }
+        self.add_dirty(Dirty::FRAME);
+    }
+
+    /// Move the focus to the next focusable node in the tree order, or to the previous
+    /// one if `backwards` is set. Wraps around at the end of the tree.
+    pub fn move_focus(&self, backwards: bool) {
+        let mut focusables = Vec::new();
+        if let Some(root) = self.root.borrow().as_ref() {
+            collect_focusables(root, &mut focusables);
+        }
+        if focusables.is_empty() {
+            return;
+        }
+        let len = focusables.len();
+        let current = self
+            .focused()
+            .and_then(|f| focusables.iter().position(|n| Rc::ptr_eq(n, &f)));
+        let next = match (current, backwards) {
+            (Some(i), false) => (i + 1) % len,
+            (Some(i), true) => (i + len - 1) % len,
+            (None, false) => 0,
+            (None, true) => len - 1,
+        };
+        self.set_focus(Some(focusables[next].clone()));
+    }
+
+    /// Get the thresholds used to recognize gestures
+    pub fn gesture_thresholds(&self) -> gesture::Thresholds {
+        self.gestures.borrow().thresholds()
+    }
+
+    /// Set the thresholds used to recognize gestures
+    pub fn set_gesture_thresholds(&self, thresholds: gesture::Thresholds) {
+        self.gestures.borrow_mut().set_thresholds(thresholds);
+    }
+
+    /// Request a new frame to be emitted, even if nothing in the view tree changed
+    pub fn request_redraw(&self) {
+        self.add_dirty(Dirty::FRAME);
+    }
+
     /// Handle a window event
     pub fn handle_event(&self, ev: winit::WindowEvent) -> winit::ControlFlow {
         match ev {
@@ -62,6 +196,108 @@ impl UserInterface {
                 self.add_dirty(Dirty::LAYOUT | Dirty::FRAME);
                 winit::ControlFlow::Continue
             }
+            winit::WindowEvent::HiDpiFactorChanged(factor) => {
+                self.scale_factor.set(factor as _);
+                self.add_dirty(Dirty::LAYOUT | Dirty::FRAME);
+                winit::ControlFlow::Continue
+            }
+            winit::WindowEvent::Refresh => {
+                self.request_redraw();
+                winit::ControlFlow::Continue
+            }
+            winit::WindowEvent::CursorMoved { position, .. } => {
+                let pos = FPoint::from(position);
+                self.cursor.set(pos);
+                self.gestures.borrow_mut().moved(pos);
+                let hit = self.node_at(pos);
+                self.set_hovered(hit.clone());
+                let pressed = self.pressed.borrow().clone();
+                if let Some(node) = pressed.or(hit) {
+                    if let Some(local) = self.to_local(&node, pos) {
+                        node.view_mut().on_pointer_move(local);
+                    }
+                }
+                winit::ControlFlow::Continue
+            }
+            winit::WindowEvent::CursorLeft { .. } => {
+                self.set_hovered(None);
+                winit::ControlFlow::Continue
+            }
+            winit::WindowEvent::MouseInput {
+                state,
+                button: winit::MouseButton::Left,
+                ..
+            } => {
+                let pos = self.cursor.get();
+                match state {
+                    winit::ElementState::Pressed => {
+                        self.gestures.borrow_mut().press(pos, Instant::now());
+                        let node = self.node_at(pos);
+                        if let Some(node) = &node {
+                            if let Some(local) = self.to_local(node, pos) {
+                                node.view_mut().on_pointer_down(local);
+                            }
+                        }
+                        *self.pressed.borrow_mut() = node;
+                    }
+                    winit::ElementState::Released => {
+                        let gesture = self.gestures.borrow_mut().release(pos, Instant::now());
+                        let node = self.pressed.borrow_mut().take();
+                        if let Some(node) = node {
+                            if let Some(local) = self.to_local(&node, pos) {
+                                node.view_mut().on_pointer_up(local);
+                                if let Some(gesture) = gesture {
+                                    self.dispatch_gesture(&node, gesture.with_position(local));
+                                }
+                            }
+                        }
+                    }
+                }
+                winit::ControlFlow::Continue
+            }
+            winit::WindowEvent::MouseWheel { delta, .. } => {
+                let delta = match delta {
+                    winit::MouseScrollDelta::LineDelta(x, y) => {
+                        geom::Vec(x * WHEEL_LINE_LENGTH, y * WHEEL_LINE_LENGTH)
+                    }
+                    winit::MouseScrollDelta::PixelDelta(pos) => geom::Vec(pos.x as f32, pos.y as f32),
+                };
+                let pos = self.cursor.get();
+                // the wheel event bubbles up until a view consumes it
+                let mut node = self.node_at(pos);
+                while let Some(n) = node {
+                    if let Some(local) = self.to_local(&n, pos) {
+                        let mut view = n.view_mut();
+                        if view.is_enabled() && view.on_wheel(local, delta) {
+                            break;
+                        }
+                    }
+                    node = n.parent();
+                }
+                winit::ControlFlow::Continue
+            }
+            winit::WindowEvent::KeyboardInput { input, .. } => {
+                // Tab and Shift-Tab are consumed for focus traversal
+                match input.virtual_keycode {
+                    Some(winit::VirtualKeyCode::Tab) => {
+                        if input.state == winit::ElementState::Pressed {
+                            self.move_focus(input.modifiers.shift);
+                        }
+                    }
+                    _ => {
+                        if let Some(node) = self.focused_enabled() {
+                            node.view_mut().on_key(input);
+                        }
+                    }
+                }
+                winit::ControlFlow::Continue
+            }
+            winit::WindowEvent::ReceivedCharacter(c) => {
+                if let Some(node) = self.focused_enabled() {
+                    node.view_mut().on_char(c);
+                }
+                winit::ControlFlow::Continue
+            }
             winit::WindowEvent::CloseRequested => {
                 winit::ControlFlow::Break
             }
@@ -72,35 +308,142 @@ impl UserInterface {
     }
 
     pub fn layout(&self) {
+        self.remove_dirty(Dirty::LAYOUT);
         if let Some(root) = self.root.borrow().as_ref() {
             let size = self.size();
+            let gravity = [self.gravity.get().horizontal(), self.gravity.get().vertical()];
+            let spec = |grav: layout::AxisGravity, size: f32| {
+                if grav.contains(layout::AxisGravity::FILL) {
+                    view::MeasureSpec::Exactly(size)
+                } else {
+                    view::MeasureSpec::AtMost(size)
+                }
+            };
             let specs = [
-                view::MeasureSpec::AtMost(size.width()),
-                view::MeasureSpec::AtMost(size.height()),
+                spec(gravity[0], size.width()),
+                spec(gravity[1], size.height()),
             ];
+            // clean subtrees are neither measured nor laid out again
             let mut root = root.view_mut();
-            root.measure(specs);
-            root.layout(FRect::new_s(0f32, 0f32, self.size()));
+            layout::measure_if_dirty(&mut *root, specs);
+            let mes = root.measurement();
+            let (x, width) = gravity[0].place(size.width(), mes.width());
+            let (y, height) = gravity[1].place(size.height(), mes.height());
+            layout::layout_if_dirty(&mut *root, FRect::new(x, y, width, height));
         }
     }
 
-    pub fn style(&self) {}
+    pub fn style(&self) {
+        self.remove_dirty(Dirty::STYLE);
+    }
 
     pub fn frame(&self, win: &Window) -> render::Frame {
-        self.remove_dirty(Dirty::FRAME);
+        let scale_factor = win.get_hidpi_factor();
         let size: (u32, u32) = win
             .get_inner_size()
-            .map(|s| s.to_physical(win.get_hidpi_factor()))
+            .map(|s| s.to_physical(scale_factor))
             .unwrap()
             .into();
+        self.frame_for(win.id(), size, scale_factor)
+    }
+
+    /// Build the frame of a window of `size` physical pixels
+    pub(crate) fn frame_for(
+        &self,
+        window: winit::WindowId,
+        size: (u32, u32),
+        scale_factor: f64,
+    ) -> render::Frame {
+        self.remove_dirty(Dirty::FRAME);
+        self.scale_factor.set(scale_factor as _);
+        let root = self.root.borrow().as_ref().and_then(frame_node);
         render::Frame::new(
-            win.id(),
+            window,
             IRect::new(0, 0, size.0 as _, size.1 as _),
+            self.scale_factor(),
             self.clear_color.get(),
-            None,
+            root,
         )
     }
 
+    /// Hit-test `node` and its subtree. `p` is in the coordinates of the node parent.
+    fn node_at_in(&self, node: &Rc<Node>, p: FPoint) -> Option<Rc<Node>> {
+        let view = node.view();
+        if view.visibility() != view::Visibility::Visible {
+            return None;
+        }
+        let enabled = view.is_enabled();
+        if !enabled && self.disabled_hit_test.get() == DisabledHitTest::SkipSubtree {
+            return None;
+        }
+        let rect = view.rect();
+        let local = view.transform().inverse()? * Point(p.0 - rect.x, p.1 - rect.y);
+        let clipped = view.frame_clip().map_or(false, |clip| !clip.contains_point(local));
+        drop(view);
+
+        let mut child = if clipped { None } else { node.last_child() };
+        while let Some(c) = child {
+            if let Some(hit) = self.node_at_in(&c, local) {
+                return Some(hit);
+            }
+            child = c.prev_sibling();
+        }
+
+        if enabled && FRect::new_s(0f32, 0f32, rect.size()).contains_point(local) {
+            Some(node.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Map the point `p`, given in window coordinates, to the local coordinates of `node`.
+    /// Returns `None` if a transform in the ancestors chain cannot be inverted.
+    fn to_local(&self, node: &Rc<Node>, p: FPoint) -> Option<FPoint> {
+        let mut chain = vec![node.clone()];
+        while let Some(parent) = chain.last().unwrap().parent() {
+            chain.push(parent);
+        }
+        chain.iter().rev().try_fold(p, |p, node| {
+            let view = node.view();
+            let rect = view.rect();
+            Some(view.transform().inverse()? * Point(p.0 - rect.x, p.1 - rect.y))
+        })
+    }
+
+    /// The focused node if it is enabled. A view disabled while focused keeps the focus
+    /// but receives no keyboard input until enabled again.
+    fn focused_enabled(&self) -> Option<Rc<Node>> {
+        self.focused().filter(|node| node.view().is_enabled())
+    }
+
+    /// Notify the views that the pointer leaves and enters if the hovered node changes
+    fn set_hovered(&self, node: Option<Rc<Node>>) {
+        let prev = self.hovered.replace(node.clone());
+        match (&prev, &node) {
+            (Some(prev), Some(node)) if Rc::ptr_eq(prev, node) => return,
+            _ => {}
+        }
+        if let Some(prev) = prev {
+            prev.view_mut().on_pointer_leave();
+        }
+        if let Some(node) = node {
+            node.view_mut().on_pointer_enter();
+        }
+    }
+
+    /// Deliver a gesture to `node`. The gesture position is in the node local coordinates.
+    fn dispatch_gesture(&self, node: &Rc<Node>, gesture: gesture::Gesture) {
+        let mut view = node.view_mut();
+        if !view.is_enabled() {
+            return;
+        }
+        match gesture {
+            gesture::Gesture::Tap(pos) => view.on_tap(pos),
+            gesture::Gesture::DoubleTap(pos) => view.on_double_tap(pos),
+            gesture::Gesture::LongPress(pos) => view.on_long_press(pos),
+        }
+    }
+
     fn add_dirty(&self, flags: Dirty) {
         let mut dirty = self.dirty.get();
         dirty.insert(flags);
@@ -114,6 +457,86 @@ impl UserInterface {
     }
 }
 
+/// Render `node` and its subtree in a frame graph node, in the coordinates of the node parent.
+/// The output of the view is followed by the outputs of its children, moved to the view
+/// rect origin and clipped to the view frame clip. The whole is then transformed by the
+/// view transform and made translucent by the view opacity.
+/// Returns `None` if the view is not visible or nothing in the subtree is rendered.
+fn frame_node(node: &Rc<Node>) -> Option<render::frame::Node> {
+    use render::frame;
+
+    node.clear_dirty(Dirty::FRAME);
+    let view = node.view();
+    if view.visibility() != view::Visibility::Visible {
+        return None;
+    }
+    let rect = view.rect();
+    let transform = view.transform();
+    let clip = view.frame_clip();
+    let opacity = view.opacity();
+    let mut nodes: Vec<frame::Node> = view.frame_render().into_iter().collect();
+    drop(view);
+
+    let mut children = Vec::new();
+    let mut child = node.first_child();
+    while let Some(c) = child {
+        children.extend(frame_node(&c));
+        child = c.next_sibling();
+    }
+    if !children.is_empty() {
+        let mut children = frame::Node::Group(children);
+        if let Some(clip) = clip {
+            children = frame::Node::Clip(clip, Box::new(children));
+        }
+        if rect.x != 0f32 || rect.y != 0f32 {
+            let origin = Transform::translation(geom::Vec(rect.x, rect.y));
+            children = frame::Node::transform(children, &origin);
+        }
+        nodes.push(children);
+    }
+
+    let mut res = match nodes.len() {
+        0 => return None,
+        1 => nodes.pop().unwrap(),
+        _ => frame::Node::Group(nodes),
+    };
+    if transform != Transform::identity() {
+        // the view transform is relative to the view rect origin
+        let transform = (transform * Transform::translation(geom::Vec(-rect.x, -rect.y)))
+            .translate(geom::Vec(rect.x, rect.y));
+        res = frame::Node::transform(res, &transform);
+    }
+    Some(res.with_opacity(opacity))
+}
+
+/// Collect in tree order the enabled and focusable nodes of the subtree of `node`.
+/// Children of disabled or invisible views are skipped.
+fn collect_focusables(node: &Rc<Node>, focusables: &mut Vec<Rc<Node>>) {
+    {
+        let view = node.view();
+        if !view.is_enabled() || view.visibility() != view::Visibility::Visible {
+            return;
+        }
+        if view.is_focusable() {
+            focusables.push(node.clone());
+        }
+    }
+    let mut child = node.first_child();
+    while let Some(c) = child {
+        collect_focusables(&c, focusables);
+        child = c.next_sibling();
+    }
+}
+
+/// How hit-testing treats disabled views
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisabledHitTest {
+    /// A disabled view and its whole subtree cannot be hit
+    SkipSubtree,
+    /// Only the disabled view cannot be hit, its enabled children still can
+    SkipView,
+}
+
 bitflags! {
     pub struct Dirty : u32 {
         const LAYOUT = 1;
@@ -121,3 +544,535 @@ bitflags! {
         const FRAME  = 4;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::frame;
+    use crate::ui::view::{Base, Children, MeasureSpec};
+
+    /// Event received by a `Probe`
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Event {
+        Down(FPoint),
+        Up(FPoint),
+        Move(FPoint),
+        Tap(FPoint),
+        Wheel(FPoint),
+        Focus(bool),
+        Key(Option<winit::VirtualKeyCode>),
+        Char(char),
+    }
+
+    /// A view of fixed size that records the events it receives
+    #[derive(Debug)]
+    pub struct Probe {
+        common: view::Common,
+        size: FSize,
+        scrollable: bool,
+        clip: Option<FRect>,
+        events: Rc<RefCell<Vec<Event>>>,
+        measures: Rc<Cell<usize>>,
+    }
+
+    impl Probe {
+        pub fn new(size: FSize) -> Probe {
+            Probe {
+                common: view::Common::default(),
+                size,
+                scrollable: false,
+                clip: None,
+                events: Rc::new(RefCell::new(Vec::new())),
+                measures: Rc::new(Cell::new(0)),
+            }
+        }
+
+        /// A probe that consumes the wheel events
+        pub fn new_scrollable(size: FSize) -> Probe {
+            Probe {
+                scrollable: true,
+                ..Probe::new(size)
+            }
+        }
+
+        /// Clip the children of the probe to `clip`, in the probe coordinates
+        pub fn set_clip(&mut self, clip: Option<FRect>) {
+            self.clip = clip;
+        }
+
+        /// The events received so far, shared with the probe
+        pub fn events(&self) -> Rc<RefCell<Vec<Event>>> {
+            self.events.clone()
+        }
+
+        /// The number of times the probe was measured, shared with the probe
+        pub fn measures(&self) -> Rc<Cell<usize>> {
+            self.measures.clone()
+        }
+    }
+
+    impl view::HasChildren for Probe {}
+
+    impl View for Probe {}
+
+    impl view::Measure for Probe {
+        fn measure(&mut self, _specs: [MeasureSpec; 2]) {
+            for node in self.children() {
+                node.view_mut().measure([MeasureSpec::Unspecified; 2]);
+            }
+            self.common.measurement = self.size;
+            self.measures.set(self.measures.get() + 1);
+        }
+    }
+
+    /// Children keep their position and are laid out at their measured size
+    impl view::Layout for Probe {
+        fn layout(&mut self, rect: FRect) {
+            self.common.rect = rect;
+            for node in self.children() {
+                let mut view = node.view_mut();
+                let pos = view.rect().point();
+                let mes = view.measurement();
+                view.layout(FRect::new_s(pos.0, pos.1, mes));
+            }
+        }
+    }
+
+    impl view::GestureHandler for Probe {
+        fn on_tap(&mut self, pos: FPoint) {
+            self.events.borrow_mut().push(Event::Tap(pos));
+        }
+    }
+
+    impl view::PointerHandler for Probe {
+        fn on_pointer_down(&mut self, pos: FPoint) {
+            self.events.borrow_mut().push(Event::Down(pos));
+        }
+        fn on_pointer_up(&mut self, pos: FPoint) {
+            self.events.borrow_mut().push(Event::Up(pos));
+        }
+        fn on_pointer_move(&mut self, pos: FPoint) {
+            self.events.borrow_mut().push(Event::Move(pos));
+        }
+        fn on_wheel(&mut self, pos: FPoint, _delta: geom::FVec) -> bool {
+            self.events.borrow_mut().push(Event::Wheel(pos));
+            self.scrollable
+        }
+    }
+
+    impl view::KeyHandler for Probe {
+        fn on_focus(&mut self, focused: bool) {
+            self.events.borrow_mut().push(Event::Focus(focused));
+        }
+        fn on_key(&mut self, input: winit::KeyboardInput) {
+            self.events.borrow_mut().push(Event::Key(input.virtual_keycode));
+        }
+        fn on_char(&mut self, c: char) {
+            self.events.borrow_mut().push(Event::Char(c));
+        }
+    }
+
+    impl view::FrameRender for Probe {
+        fn frame_render(&self) -> Option<frame::Node> {
+            None
+        }
+        fn frame_clip(&self) -> Option<FRect> {
+            self.clip
+        }
+    }
+
+    impl view::Base for Probe {
+        type State = ();
+        type Style = ();
+
+        fn common(&self) -> &view::Common {
+            &self.common
+        }
+        fn common_mut(&mut self) -> &mut view::Common {
+            &mut self.common
+        }
+    }
+
+    /// Render the frame graph of the user interface, as `UserInterface::frame` does
+    pub fn render(ui: &UserInterface) -> Option<frame::Node> {
+        ui.remove_dirty(Dirty::FRAME);
+        ui.root.borrow().as_ref().and_then(frame_node)
+    }
+
+    fn device() -> winit::DeviceId {
+        unsafe { winit::DeviceId::dummy() }
+    }
+
+    /// Give `root` to `ui`, resize it to `size` and lay it out
+    pub fn set_up(ui: &UserInterface, root: &Rc<Node>, size: (f64, f64)) {
+        ui.set_root(Some(root.clone()));
+        ui.handle_event(winit::WindowEvent::Resized(winit::dpi::LogicalSize::new(
+            size.0, size.1,
+        )));
+        ui.layout();
+    }
+
+    pub fn move_to(ui: &UserInterface, x: f64, y: f64) {
+        ui.handle_event(winit::WindowEvent::CursorMoved {
+            device_id: device(),
+            position: winit::dpi::LogicalPosition::new(x, y),
+            modifiers: winit::ModifiersState::default(),
+        });
+    }
+
+    fn mouse_input(ui: &UserInterface, state: winit::ElementState) {
+        ui.handle_event(winit::WindowEvent::MouseInput {
+            device_id: device(),
+            state,
+            button: winit::MouseButton::Left,
+            modifiers: winit::ModifiersState::default(),
+        });
+    }
+
+    pub fn press(ui: &UserInterface) {
+        mouse_input(ui, winit::ElementState::Pressed);
+    }
+
+    pub fn release(ui: &UserInterface) {
+        mouse_input(ui, winit::ElementState::Released);
+    }
+
+    /// Scroll the wheel by `lines` vertically, at the cursor position
+    pub fn wheel(ui: &UserInterface, lines: f32) {
+        ui.handle_event(winit::WindowEvent::MouseWheel {
+            device_id: device(),
+            delta: winit::MouseScrollDelta::LineDelta(0f32, lines),
+            phase: winit::TouchPhase::Moved,
+            modifiers: winit::ModifiersState::default(),
+        });
+    }
+
+    pub fn key(ui: &UserInterface, key: winit::VirtualKeyCode, shift: bool) {
+        let modifiers = winit::ModifiersState {
+            shift,
+            ..Default::default()
+        };
+        for &state in &[winit::ElementState::Pressed, winit::ElementState::Released] {
+            ui.handle_event(winit::WindowEvent::KeyboardInput {
+                device_id: device(),
+                input: winit::KeyboardInput {
+                    scancode: 0,
+                    state,
+                    virtual_keycode: Some(key),
+                    modifiers,
+                },
+            });
+        }
+    }
+
+    /// A probe at `pos` within its parent, and its events
+    pub fn probe_at(
+        ui: &Rc<UserInterface>,
+        pos: (f32, f32),
+        size: (f32, f32),
+    ) -> (Rc<Node>, Rc<RefCell<Vec<Event>>>) {
+        let mut probe = Probe::new(Size(size.0, size.1));
+        probe.common.rect = FRect::new(pos.0, pos.1, size.0, size.1);
+        let events = probe.events();
+        (Node::new(probe, ui.clone(), None), events)
+    }
+
+    /// Three focusable probes in a root probe
+    fn focus_tree() -> (Rc<UserInterface>, Vec<Rc<Node>>, Vec<Rc<RefCell<Vec<Event>>>>) {
+        let ui = UserInterface::new();
+        let (root, _) = probe_at(&ui, (0f32, 0f32), (100f32, 100f32));
+        let mut nodes = Vec::new();
+        let mut events = Vec::new();
+        for i in 0..3 {
+            let (node, ev) = probe_at(&ui, (0f32, 10f32 * i as f32), (10f32, 10f32));
+            node.view_mut().set_focusable(true);
+            root.add_child(&node, None);
+            nodes.push(node);
+            events.push(ev);
+        }
+        set_up(&ui, &root, (100f64, 100f64));
+        (ui, nodes, events)
+    }
+
+    #[test]
+    fn key_to_focused() {
+        let (ui, nodes, events) = focus_tree();
+        ui.set_focus(Some(nodes[1].clone()));
+        key(&ui, winit::VirtualKeyCode::A, false);
+        ui.handle_event(winit::WindowEvent::ReceivedCharacter('a'));
+        let a = Some(winit::VirtualKeyCode::A);
+        assert_eq!(
+            *events[1].borrow(),
+            vec![Event::Focus(true), Event::Key(a), Event::Key(a), Event::Char('a')]
+        );
+        assert!(events[0].borrow().is_empty());
+        assert!(events[2].borrow().is_empty());
+    }
+
+    #[test]
+    fn key_not_to_disabled() {
+        let (ui, nodes, events) = focus_tree();
+        ui.set_focus(Some(nodes[0].clone()));
+        nodes[0].view_mut().set_enabled(false);
+        key(&ui, winit::VirtualKeyCode::A, false);
+        ui.handle_event(winit::WindowEvent::ReceivedCharacter('a'));
+        assert_eq!(*events[0].borrow(), vec![Event::Focus(true)]);
+        nodes[0].view_mut().set_enabled(true);
+        ui.handle_event(winit::WindowEvent::ReceivedCharacter('b'));
+        assert_eq!(*events[0].borrow(), vec![Event::Focus(true), Event::Char('b')]);
+    }
+
+    #[test]
+    fn focus_traversal() {
+        let (ui, nodes, events) = focus_tree();
+        assert!(ui.focused().is_none());
+        key(&ui, winit::VirtualKeyCode::Tab, false);
+        assert!(ui.focused().unwrap().is_same(&nodes[0]));
+        key(&ui, winit::VirtualKeyCode::Tab, false);
+        assert!(ui.focused().unwrap().is_same(&nodes[1]));
+        key(&ui, winit::VirtualKeyCode::Tab, false);
+        assert!(ui.focused().unwrap().is_same(&nodes[2]));
+        key(&ui, winit::VirtualKeyCode::Tab, false);
+        assert!(ui.focused().unwrap().is_same(&nodes[0]));
+        key(&ui, winit::VirtualKeyCode::Tab, true);
+        assert!(ui.focused().unwrap().is_same(&nodes[2]));
+        assert_eq!(
+            *events[0].borrow(),
+            vec![Event::Focus(true), Event::Focus(false), Event::Focus(true), Event::Focus(false)]
+        );
+        // Tab is not delivered to the focused view
+        assert!(!events[2].borrow().iter().any(|e| match *e {
+            Event::Key(_) => true,
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn focus_traversal_skips_non_focusable_and_disabled() {
+        let (ui, nodes, _events) = focus_tree();
+        nodes[1].view_mut().set_focusable(false);
+        nodes[2].view_mut().set_enabled(false);
+        key(&ui, winit::VirtualKeyCode::Tab, false);
+        assert!(ui.focused().unwrap().is_same(&nodes[0]));
+        key(&ui, winit::VirtualKeyCode::Tab, false);
+        assert!(ui.focused().unwrap().is_same(&nodes[0]));
+    }
+
+    #[test]
+    fn set_focusable_invalidates() {
+        let (ui, nodes, _events) = focus_tree();
+        render(&ui);
+        assert!(!nodes[0].dirty().contains(Dirty::FRAME));
+        nodes[0].view_mut().set_focusable(false);
+        assert!(nodes[0].dirty().contains(Dirty::FRAME));
+        assert!(ui.dirty(Dirty::FRAME));
+    }
+
+    /// A root probe of 200x200 with
+    /// - a probe at (20, 20) of 100x100, with a child at (10, 10) of 20x20
+    /// - a probe at (150, 0) of 40x40 scaled by 2, with a child at the origin of 10x10
+    fn hit_tree() -> (Rc<UserInterface>, [Rc<Node>; 5]) {
+        let ui = UserInterface::new();
+        let (root, _) = probe_at(&ui, (0f32, 0f32), (200f32, 200f32));
+        let (a, _) = probe_at(&ui, (20f32, 20f32), (100f32, 100f32));
+        let (a1, _) = probe_at(&ui, (10f32, 10f32), (20f32, 20f32));
+        let (t, _) = probe_at(&ui, (150f32, 0f32), (40f32, 40f32));
+        let (t1, _) = probe_at(&ui, (0f32, 0f32), (10f32, 10f32));
+        t.view_as_mut::<Probe>().set_transform(Transform::homothetie([2f32, 2f32]));
+        a.add_child(&a1, None);
+        t.add_child(&t1, None);
+        root.add_child(&a, None);
+        root.add_child(&t, None);
+        set_up(&ui, &root, (300f64, 300f64));
+        (ui, [root, a, a1, t, t1])
+    }
+
+    fn hit(ui: &UserInterface, x: f32, y: f32) -> Option<Rc<Node>> {
+        ui.node_at(Point(x, y))
+    }
+
+    #[test]
+    fn hit_nested() {
+        let (ui, [root, a, a1, _, _]) = hit_tree();
+        assert!(hit(&ui, 35f32, 35f32).unwrap().is_same(&a1));
+        assert!(hit(&ui, 25f32, 25f32).unwrap().is_same(&a));
+        assert!(hit(&ui, 5f32, 5f32).unwrap().is_same(&root));
+        assert!(hit(&ui, 250f32, 250f32).is_none());
+    }
+
+    #[test]
+    fn hit_transformed() {
+        let (ui, [_, _, _, t, t1]) = hit_tree();
+        assert!(hit(&ui, 165f32, 15f32).unwrap().is_same(&t1));
+        assert!(hit(&ui, 175f32, 15f32).unwrap().is_same(&t));
+        // out of the root, but within the scaled view
+        assert!(hit(&ui, 210f32, 60f32).unwrap().is_same(&t));
+        assert!(hit(&ui, 235f32, 60f32).is_none());
+    }
+
+    #[test]
+    fn hit_disabled_skip_subtree() {
+        let (ui, [root, a, _, _, _]) = hit_tree();
+        assert_eq!(ui.disabled_hit_test(), DisabledHitTest::SkipSubtree);
+        a.view_mut().set_enabled(false);
+        assert!(hit(&ui, 35f32, 35f32).unwrap().is_same(&root));
+        assert!(hit(&ui, 25f32, 25f32).unwrap().is_same(&root));
+    }
+
+    #[test]
+    fn hit_disabled_skip_view() {
+        let (ui, [root, a, a1, _, _]) = hit_tree();
+        ui.set_disabled_hit_test(DisabledHitTest::SkipView);
+        a.view_mut().set_enabled(false);
+        assert!(hit(&ui, 35f32, 35f32).unwrap().is_same(&a1));
+        assert!(hit(&ui, 25f32, 25f32).unwrap().is_same(&root));
+    }
+
+    /// Two probes of 50x50 at (20, 20) and (100, 20) in a root probe
+    fn pointer_tree() -> (Rc<UserInterface>, [Rc<RefCell<Vec<Event>>>; 3]) {
+        let ui = UserInterface::new();
+        let (root, root_ev) = probe_at(&ui, (0f32, 0f32), (200f32, 200f32));
+        let (a, a_ev) = probe_at(&ui, (20f32, 20f32), (50f32, 50f32));
+        let (b, b_ev) = probe_at(&ui, (100f32, 20f32), (50f32, 50f32));
+        root.add_child(&a, None);
+        root.add_child(&b, None);
+        set_up(&ui, &root, (300f64, 300f64));
+        (ui, [root_ev, a_ev, b_ev])
+    }
+
+    #[test]
+    fn pointer_press_release() {
+        let (ui, [root, a, b]) = pointer_tree();
+        move_to(&ui, 30f64, 30f64);
+        press(&ui);
+        release(&ui);
+        let p = Point(10f32, 10f32);
+        assert_eq!(
+            *a.borrow(),
+            vec![Event::Move(p), Event::Down(p), Event::Up(p), Event::Tap(p)]
+        );
+        assert!(root.borrow().is_empty());
+        assert!(b.borrow().is_empty());
+    }
+
+    #[test]
+    fn pointer_capture() {
+        let (ui, [root, a, b]) = pointer_tree();
+        move_to(&ui, 30f64, 30f64);
+        press(&ui);
+        a.borrow_mut().clear();
+        // the pressed view receives the moves and the release, even out of it
+        move_to(&ui, 110f64, 30f64);
+        move_to(&ui, 5f64, 150f64);
+        release(&ui);
+        assert_eq!(
+            *a.borrow(),
+            vec![
+                Event::Move(Point(90f32, 10f32)),
+                Event::Move(Point(-15f32, 130f32)),
+                Event::Up(Point(-15f32, 130f32)),
+            ]
+        );
+        assert!(root.borrow().is_empty());
+        assert!(b.borrow().is_empty());
+
+        // once released, the moves go to the view under the pointer again
+        move_to(&ui, 110f64, 30f64);
+        assert_eq!(*b.borrow(), vec![Event::Move(Point(10f32, 10f32))]);
+        assert_eq!(a.borrow().len(), 3);
+    }
+
+    #[test]
+    fn pointer_press_outside() {
+        let (ui, [root, a, b]) = pointer_tree();
+        move_to(&ui, 250f64, 250f64);
+        press(&ui);
+        move_to(&ui, 30f64, 30f64);
+        release(&ui);
+        // nothing was pressed, so the release is delivered to no view
+        assert_eq!(*a.borrow(), vec![Event::Move(Point(10f32, 10f32))]);
+        assert!(root.borrow().is_empty());
+        assert!(b.borrow().is_empty());
+    }
+
+    fn assert_rect(node: &frame::Node, expected: FRect) {
+        match node {
+            frame::Node::Rect { rect, .. } => assert_eq!(*rect, expected),
+            _ => panic!("expected a rect node"),
+        }
+    }
+
+    #[test]
+    fn frame_two_labels() {
+        let ui = UserInterface::new();
+        let root = Node::new(LinearLayout::new_vertical(), ui.clone(), None);
+        for &(w, h) in &[(10f32, 10f32), (20f32, 5f32)] {
+            let mut label = Label::default();
+            label.set_min_size(Size(w, h));
+            root.add_child(&Node::new(label, ui.clone(), None), None);
+        }
+        set_up(&ui, &root, (100f64, 100f64));
+        match render(&ui) {
+            Some(frame::Node::Group(nodes)) => {
+                assert_eq!(nodes.len(), 2);
+                assert_rect(&nodes[0], FRect::new(0f32, 0f32, 10f32, 10f32));
+                assert_rect(&nodes[1], FRect::new(0f32, 10f32, 20f32, 5f32));
+            }
+            _ => panic!("expected a group of the two labels"),
+        }
+        assert!(!ui.dirty(Dirty::FRAME));
+        assert!(!root.dirty().contains(Dirty::FRAME));
+    }
+
+    #[test]
+    fn frame_clip_and_opacity() {
+        let ui = UserInterface::new();
+        let (root, _) = probe_at(&ui, (0f32, 0f32), (200f32, 200f32));
+        let (c, _) = probe_at(&ui, (20f32, 30f32), (100f32, 100f32));
+        let clip = FRect::new(0f32, 0f32, 50f32, 50f32);
+        c.view_as_mut::<Probe>().set_clip(Some(clip));
+        c.view_as_mut::<Probe>().set_opacity(0.5f32);
+        let mut label = Label::default();
+        label.set_min_size(Size(10f32, 10f32));
+        c.add_child(&Node::new(label, ui.clone(), None), None);
+        root.add_child(&c, None);
+        set_up(&ui, &root, (300f64, 300f64));
+
+        let children = match render(&ui) {
+            Some(frame::Node::Group(children)) => children,
+            _ => panic!("expected the group of the root children"),
+        };
+        assert_eq!(children.len(), 1);
+        let translated = match &children[0] {
+            frame::Node::Opacity(opacity, child) => {
+                assert_eq!(*opacity, 0.5f32);
+                child
+            }
+            _ => panic!("expected an opacity node"),
+        };
+        let clipped = match &**translated {
+            frame::Node::Transform(child, mat) => {
+                let origin = Transform::translation(geom::Vec(20f32, 30f32));
+                assert_eq!(*mat, origin.to_4x4_col_major());
+                child
+            }
+            _ => panic!("expected the translation to the view origin"),
+        };
+        match &**clipped {
+            frame::Node::Clip(rect, child) => {
+                assert_eq!(*rect, clip);
+                match &**child {
+                    frame::Node::Group(nodes) => {
+                        assert_eq!(nodes.len(), 1);
+                        assert_rect(&nodes[0], FRect::new(0f32, 0f32, 10f32, 10f32));
+                    }
+                    _ => panic!("expected the group of the children"),
+                }
+            }
+            _ => panic!("expected a clip node"),
+        }
+
+        c.view_as_mut::<Probe>().set_visibility(view::Visibility::Hidden);
+        assert!(render(&ui).is_none());
+    }
+}
@@ -1,13 +1,14 @@
-use super::{UserInterface, View};
+use super::{Dirty, UserInterface, View};
 use super::view::Base;
 use super::view::Common;
-use std::cell::{Ref, RefCell, RefMut};
+use super::view::MeasureSpec;
+use std::cell::{Cell, Ref, RefCell, RefMut};
 use std::rc::{Rc, Weak};
 
 mod id {
     use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
 
-    const NEXT: AtomicUsize = ATOMIC_USIZE_INIT;
+    static NEXT: AtomicUsize = ATOMIC_USIZE_INIT;
 
     #[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
     pub struct Id(usize);
@@ -25,6 +26,8 @@ use id::Id;
 pub struct Node {
     id: Id,
     view: RefCell<Box<dyn View>>,
+    /// Type name of the view, for debugging
+    view_type: &'static str,
     ui: Weak<UserInterface>,
     me: RefCell<Weak<Node>>,
     parent: RefCell<Option<Weak<Node>>>,
@@ -32,6 +35,8 @@ pub struct Node {
     last_child: RefCell<Option<Weak<Node>>>,
     prev_sibling: RefCell<Option<Weak<Node>>>,
     next_sibling: RefCell<Option<Rc<Node>>>,
+    dirty: Cell<Dirty>,
+    measured_specs: Cell<Option<[MeasureSpec; 2]>>,
 }
 
 impl Node {
@@ -44,6 +49,7 @@ impl Node {
         let node = Rc::new(Node {
             id: Id::next(),
             view: RefCell::new(boxed),
+            view_type: std::any::type_name::<V>(),
             ui: Rc::downgrade(&ui),
             me: RefCell::new(Weak::default()),
             parent: RefCell::new(parent.map(|n| Rc::downgrade(&n))),
@@ -51,6 +57,8 @@ impl Node {
             last_child: RefCell::new(None),
             prev_sibling: RefCell::new(None),
             next_sibling: RefCell::new(None),
+            dirty: Cell::new(Dirty::all()),
+            measured_specs: Cell::new(None),
         });
         *node.me.borrow_mut() = Rc::downgrade(&node);
         let mut common = unsafe { &mut *common };
@@ -84,6 +92,24 @@ impl Node {
         })
     }
 
+    /// Checks whether the view of this node is a `V`
+    pub fn is<V>(&self) -> bool
+        where V: View
+    {
+        self.view().is::<V>()
+    }
+
+    /// Get the view as a `V`, or `None` if the view is of another type
+    pub fn try_view_as<V>(&self) -> Option<Ref<V>>
+        where V: View
+    {
+        if self.is::<V>() {
+            Some(self.view_as::<V>())
+        } else {
+            None
+        }
+    }
+
     pub fn view_as_mut<V>(&self) -> RefMut<V>
         where V: View
     {
@@ -92,6 +118,35 @@ impl Node {
         })
     }
 
+    /// Get the view mutably as a `V`, or `None` if the view is of another type
+    pub fn try_view_as_mut<V>(&self) -> Option<RefMut<V>>
+        where V: View
+    {
+        if self.is::<V>() {
+            Some(self.view_as_mut::<V>())
+        } else {
+            None
+        }
+    }
+
+    /// Get the dirty flags of this node. A flag set on a node is also set on its ancestors.
+    pub fn dirty(&self) -> Dirty {
+        self.dirty.get()
+    }
+
+    /// Mark this node and its ancestors with the given dirty flags,
+    /// as well as the user interface owning them
+    pub fn mark_dirty(&self, flags: Dirty) {
+        let mut node = Some(self.me());
+        while let Some(n) = node {
+            n.mark_dirty_self(flags);
+            node = n.parent();
+        }
+        if let Some(ui) = self.ui.upgrade() {
+            ui.add_dirty(flags);
+        }
+    }
+
     pub fn parent(&self) -> Option<Rc<Node>> {
         match self.parent.borrow().as_ref() {
             None => None,
@@ -123,11 +178,18 @@ impl Node {
 
     pub fn has_children(&self) -> bool {
         debug_assert!(self.first_child.borrow().is_some() == self.last_child.borrow().is_some());
+        debug_assert!(self
+            .first_child()
+            .map_or(true, |first| first.prev_sibling().is_none()));
+        debug_assert!(self
+            .last_child()
+            .map_or(true, |last| last.next_sibling().is_none()));
         self.first_child.borrow().is_some()
     }
 
     pub fn add_child(&self, node: &Rc<Node>, before: Option<&Rc<Node>>) {
         node.set_parent(Some(&self.me()));
+        self.mark_dirty(Dirty::LAYOUT | Dirty::FRAME);
         if !self.has_children() {
             assert!(before.is_none());
             self.set_first_child(Some(node));
@@ -154,10 +216,154 @@ impl Node {
             }
         }
     }
+
+    /// Remove `node` from the children of self
+    pub fn remove_child(&self, node: &Rc<Node>) {
+        assert!(
+            node.parent().map_or(false, |p| p.is_same(self)),
+            "attempt to remove a node that is not a child"
+        );
+        let prev = node.prev_sibling();
+        let next = node.next_sibling();
+        match prev.as_ref() {
+            Some(prev) => prev.set_next_sibling(next.as_ref()),
+            None => self.set_first_child(next.as_ref()),
+        }
+        match next.as_ref() {
+            Some(next) => next.set_prev_sibling(prev.as_ref()),
+            None => self.set_last_child(prev.as_ref()),
+        }
+        node.set_parent(None);
+        node.set_prev_sibling(None);
+        node.set_next_sibling(None);
+        self.mark_dirty(Dirty::LAYOUT | Dirty::FRAME);
+    }
+
+    /// Describe this node and its subtree, one node per line indented by depth,
+    /// with the view type, measurement and rect
+    pub fn dump(&self) -> String {
+        let mut out = String::new();
+        self.dump_in(0, &mut out);
+        out
+    }
+
+    fn dump_in(&self, depth: usize, out: &mut String) {
+        {
+            let view = self.view();
+            // the type name without its module path
+            let name = self.view_type.rsplit("::").next().unwrap_or(self.view_type);
+            out.push_str(&format!(
+                "{:indent$}{} measurement: {:?}, rect: {:?}\n",
+                "",
+                name,
+                view.measurement(),
+                view.rect(),
+                indent = 2 * depth
+            ));
+        }
+        let mut child = self.first_child();
+        while let Some(c) = child {
+            c.dump_in(depth + 1, out);
+            child = c.next_sibling();
+        }
+    }
+
+    /// Remove self from the children of its parent, if any
+    pub fn detach(&self) {
+        if let Some(parent) = self.parent() {
+            parent.remove_child(&self.me());
+        }
+    }
+}
+
+/// Builds a tree of nodes of the same user interface.
+/// Children are linked in the order they are given, as `Node::add_child` would.
+///
+/// ```ignore
+/// let root = NodeBuilder::new(ui.clone(), LinearLayout::new_vertical())
+///     .child(Label::default())
+///     .child_with(LinearLayout::new_horizontal(), |row| {
+///         row.children(vec![Label::default(), Label::default()])
+///     })
+///     .build();
+/// ```
+pub struct NodeBuilder {
+    ui: Rc<UserInterface>,
+    node: Rc<Node>,
+}
+
+impl NodeBuilder {
+    /// Start a tree whose root has the given view
+    pub fn new<V>(ui: Rc<UserInterface>, view: V) -> NodeBuilder
+    where
+        V: Base + 'static,
+    {
+        let node = Node::new(view, ui.clone(), None);
+        NodeBuilder { ui, node }
+    }
+
+    /// The node being built
+    pub fn node(&self) -> &Rc<Node> {
+        &self.node
+    }
+
+    /// Add a child with the given view
+    pub fn child<V>(self, view: V) -> NodeBuilder
+    where
+        V: Base + 'static,
+    {
+        self.child_with(view, |child| child)
+    }
+
+    /// Add a child with the given view, whose subtree is built by `build`
+    pub fn child_with<V, F>(self, view: V, build: F) -> NodeBuilder
+    where
+        V: Base + 'static,
+        F: FnOnce(NodeBuilder) -> NodeBuilder,
+    {
+        let child = build(NodeBuilder::new(self.ui.clone(), view));
+        self.node.add_child(&child.node, None);
+        self
+    }
+
+    /// Add an already built node as a child
+    pub fn child_node(self, node: &Rc<Node>) -> NodeBuilder {
+        self.node.add_child(node, None);
+        self
+    }
+
+    /// Add a child for each view
+    pub fn children<I>(self, views: I) -> NodeBuilder
+    where
+        I: IntoIterator,
+        I::Item: Base + 'static,
+    {
+        views.into_iter().fold(self, |builder, view| builder.child(view))
+    }
+
+    /// Get the root node of the built tree
+    pub fn build(self) -> Rc<Node> {
+        self.node
+    }
 }
 
 impl Node
 {
+    /// Set the given dirty flags on this node only
+    pub(super) fn mark_dirty_self(&self, flags: Dirty) {
+        self.dirty.set(self.dirty.get() | flags);
+    }
+    /// Clear the given dirty flags of this node only
+    pub(super) fn clear_dirty(&self, flags: Dirty) {
+        self.dirty.set(self.dirty.get() - flags);
+    }
+    /// The specs of the last measure of the view
+    pub(super) fn measured_specs(&self) -> Option<[MeasureSpec; 2]> {
+        self.measured_specs.get()
+    }
+    pub(super) fn set_measured_specs(&self, specs: [MeasureSpec; 2]) {
+        self.measured_specs.set(Some(specs));
+    }
     fn me(&self) -> Rc<Node> {
         self.me.borrow().upgrade().unwrap()
     }
@@ -177,3 +383,154 @@ impl Node
         *self.next_sibling.borrow_mut() = node.map(Rc::clone);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::Size;
+    use crate::render::frame::Image;
+    use crate::Color;
+    use std::sync::Arc;
+    use crate::ui::tests::set_up;
+    use crate::ui::{Label, LinearLayout, Picture};
+
+    fn child_count(node: &Node) -> usize {
+        let mut count = 0;
+        let mut child = node.first_child();
+        while let Some(c) = child {
+            count += 1;
+            child = c.next_sibling();
+        }
+        count
+    }
+
+    fn three_children() -> (Rc<UserInterface>, Rc<Node>, Vec<Rc<Node>>) {
+        let ui = UserInterface::new();
+        let parent = Node::new(LinearLayout::new_vertical(), ui.clone(), None);
+        let children: Vec<_> = (0..3)
+            .map(|_| Node::new(Label::default(), ui.clone(), None))
+            .collect();
+        for c in &children {
+            parent.add_child(c, None);
+        }
+        (ui, parent, children)
+    }
+
+    #[test]
+    fn remove_middle_then_first() {
+        let (_ui, parent, c) = three_children();
+        assert_eq!(child_count(&parent), 3);
+
+        parent.remove_child(&c[1]);
+        assert_eq!(child_count(&parent), 2);
+        assert!(c[1].parent().is_none());
+        assert!(c[1].prev_sibling().is_none());
+        assert!(c[1].next_sibling().is_none());
+        assert!(c[0].next_sibling().unwrap().is_same(&c[2]));
+        assert!(c[2].prev_sibling().unwrap().is_same(&c[0]));
+
+        parent.remove_child(&c[0]);
+        assert_eq!(child_count(&parent), 1);
+        assert!(c[0].parent().is_none());
+        assert!(c[0].next_sibling().is_none());
+        assert!(parent.first_child().unwrap().is_same(&c[2]));
+        assert!(parent.last_child().unwrap().is_same(&c[2]));
+        assert!(c[2].prev_sibling().is_none());
+        assert!(c[2].next_sibling().is_none());
+        assert!(c[2].parent().unwrap().is_same(&parent));
+    }
+
+    #[test]
+    fn detach() {
+        let (_ui, parent, c) = three_children();
+        c[2].detach();
+        assert_eq!(child_count(&parent), 2);
+        assert!(parent.last_child().unwrap().is_same(&c[1]));
+        assert!(c[1].next_sibling().is_none());
+        c[0].detach();
+        c[1].detach();
+        assert!(!parent.has_children());
+        // detaching a node without parent does nothing
+        c[1].detach();
+        assert!(c[1].parent().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "not a child")]
+    fn remove_non_child() {
+        let (ui, parent, _c) = three_children();
+        let other = Node::new(Label::default(), ui, None);
+        parent.remove_child(&other);
+    }
+
+    #[test]
+    fn ids_are_unique() {
+        let ui = UserInterface::new();
+        let a = Node::new(Label::default(), ui.clone(), None);
+        let b = Node::new(Label::default(), ui, None);
+        assert_ne!(a.id, b.id);
+        assert!(!a.is_same(&b));
+        assert!(a.is_same(&a));
+    }
+
+    #[test]
+    fn try_view_as() {
+        let ui = UserInterface::new();
+        let label = Label::new_with_text("hello".to_string(), Color::new(0, 0, 0, 255));
+        let node = Node::new(label, ui, None);
+        assert_eq!(node.try_view_as::<Label>().unwrap().text(), "hello");
+        node.try_view_as_mut::<Label>().unwrap().set_text("world".to_string());
+        assert_eq!(node.view_as::<Label>().text(), "world");
+    }
+
+    #[test]
+    fn try_view_as_wrong_type() {
+        let ui = UserInterface::new();
+        let node = Node::new(Label::default(), ui, None);
+        assert!(node.try_view_as::<LinearLayout>().is_none());
+        assert!(node.try_view_as_mut::<LinearLayout>().is_none());
+    }
+
+    #[test]
+    fn dump() {
+        let ui = UserInterface::new();
+        let label = |w, h| {
+            let mut label = Label::default();
+            label.set_min_size(Size(w, h));
+            label
+        };
+        let root = NodeBuilder::new(ui.clone(), LinearLayout::new_vertical())
+            .child(label(40f32, 10f32))
+            .child_with(LinearLayout::new_horizontal(), |row| {
+                row.children(vec![label(20f32, 5f32), label(30f32, 5f32)])
+            })
+            .build();
+        set_up(&ui, &root, (100f64, 100f64));
+        let dump = ui.dump_tree();
+        assert_eq!(dump, root.dump());
+        let lines: Vec<&str> = dump.lines().collect();
+        let expected = [
+            "LinearLayout measurement: Size(50.0, 15.0), \
+             rect: Rect { x: 0.0, y: 0.0, width: 50.0, height: 15.0 }",
+            "  Label measurement: Size(40.0, 10.0), \
+             rect: Rect { x: 0.0, y: 0.0, width: 40.0, height: 10.0 }",
+            "  LinearLayout measurement: Size(50.0, 5.0), \
+             rect: Rect { x: 0.0, y: 10.0, width: 50.0, height: 5.0 }",
+            "    Label measurement: Size(20.0, 5.0), \
+             rect: Rect { x: 0.0, y: 0.0, width: 20.0, height: 5.0 }",
+            "    Label measurement: Size(30.0, 5.0), \
+             rect: Rect { x: 20.0, y: 0.0, width: 30.0, height: 5.0 }",
+        ];
+        assert_eq!(lines, expected);
+    }
+
+    #[test]
+    fn dump_does_not_print_view_data() {
+        let ui = UserInterface::new();
+        let image = Image::new(Size(64, 64), vec![0u8; 4 * 64 * 64]);
+        let node = Node::new(Picture::new(Arc::new(image)), ui, None);
+        let dump = node.dump();
+        assert!(dump.starts_with("Picture measurement: "), "{}", dump);
+        assert!(dump.len() < 200, "{}", dump);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::geom::{FRect, Size};
+use crate::render::frame;
+use crate::ui;
+use crate::ui::layout::resolve_size;
+use crate::ui::view::{self, Base, HasRect, MeasureSpec, View};
+
+use std::sync::Arc;
+
+/// A view displaying an image, or a part of it.
+/// Its natural size is the size of the displayed part, one pixel per logical unit.
+#[derive(Debug)]
+pub struct Picture {
+    common: view::Common,
+    image: Arc<frame::Image>,
+    src: FRect,
+}
+
+impl Picture {
+    /// Build a picture displaying the whole image
+    pub fn new(image: Arc<frame::Image>) -> Picture {
+        let size = image.size();
+        Picture {
+            common: view::Common::default(),
+            image,
+            src: FRect::new(0f32, 0f32, size.width() as f32, size.height() as f32),
+        }
+    }
+
+    pub fn image(&self) -> &Arc<frame::Image> {
+        &self.image
+    }
+
+    /// Set the image and display it whole
+    pub fn set_image(&mut self, image: Arc<frame::Image>) {
+        let size = image.size();
+        self.image = image;
+        self.src = FRect::new(0f32, 0f32, size.width() as f32, size.height() as f32);
+        self.common.invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+
+    /// The displayed part of the image, in pixels
+    pub fn src(&self) -> FRect {
+        self.src
+    }
+
+    /// Display only a part of the image, e.g. a sprite of a sprite sheet
+    pub fn set_src(&mut self, src: FRect) {
+        self.src = src;
+        self.common.invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+}
+
+impl View for Picture {}
+
+impl view::Measure for Picture {
+    fn measure(&mut self, specs: [MeasureSpec; 2]) {
+        let size = Size(
+            resolve_size(self.src.width.abs(), specs[0]),
+            resolve_size(self.src.height.abs(), specs[1]),
+        );
+        self.set_constrained_measurement(size, specs);
+    }
+}
+
+impl view::Layout for Picture {
+    fn layout(&mut self, rect: FRect) {
+        self.common.rect = rect;
+    }
+}
+
+impl view::GestureHandler for Picture {}
+
+impl view::PointerHandler for Picture {}
+
+impl view::KeyHandler for Picture {}
+
+impl view::FrameRender for Picture {
+    fn frame_render(&self) -> Option<frame::Node> {
+        Some(frame::Node::Image {
+            rect: self.rect(),
+            image: self.image.clone(),
+            src: self.src,
+        })
+    }
+}
+
+impl view::Base for Picture {
+    type State = ();
+    type Style = ();
+
+    fn common(&self) -> &view::Common {
+        &self.common
+    }
+    fn common_mut(&mut self) -> &mut view::Common {
+        &mut self.common
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::view::{Measure, Measurement};
+
+    #[test]
+    fn measure() {
+        let image = frame::Image::new(Size(64, 32), vec![0u8; 4 * 64 * 32]);
+        let mut picture = Picture::new(Arc::new(image));
+        picture.measure([MeasureSpec::Unspecified, MeasureSpec::Unspecified]);
+        assert_eq!(picture.measurement(), Size(64f32, 32f32));
+
+        // the natural size is the one of the displayed part
+        picture.set_src(FRect::new(8f32, 8f32, 16f32, 8f32));
+        picture.measure([MeasureSpec::Unspecified, MeasureSpec::Unspecified]);
+        assert_eq!(picture.measurement(), Size(16f32, 8f32));
+        picture.measure([MeasureSpec::AtMost(10f32), MeasureSpec::AtLeast(12f32)]);
+        assert_eq!(picture.measurement(), Size(10f32, 12f32));
+    }
+}
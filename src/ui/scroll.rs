@@ -0,0 +1,232 @@
+use crate::geom::{FPoint, FRect, FVec, Size};
+use crate::render::frame;
+use crate::ui::layout::{
+    child_measure_spec, layout_if_dirty, measure_if_dirty, resolve_size, Orientation,
+};
+use crate::ui::view::{self, Base, Children, HasPadding, MeasureSpec, View};
+use crate::ui;
+
+/// A container showing a part of a single child larger than itself.
+/// The child is measured without constraint along the scroll axis and
+/// is moved by the scroll offset. The child is clipped to the padded rect of the view.
+#[derive(Debug)]
+pub struct ScrollView {
+    common: view::Common,
+    orientation: Orientation,
+    offset: f32,
+    content_length: f32,
+}
+
+impl view::HasChildren for ScrollView {}
+
+impl ScrollView {
+    pub fn new(orientation: Orientation) -> ScrollView {
+        ScrollView {
+            common: view::Common::default(),
+            orientation,
+            offset: 0f32,
+            content_length: 0f32,
+        }
+    }
+
+    pub fn new_horizontal() -> ScrollView {
+        Self::new(Orientation::Horizontal)
+    }
+
+    pub fn new_vertical() -> ScrollView {
+        Self::new(Orientation::Vertical)
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// The distance the child is scrolled by along the scroll axis
+    pub fn offset(&self) -> f32 {
+        self.offset
+    }
+
+    /// The largest offset, at which the end of the child is visible
+    pub fn max_offset(&self) -> f32 {
+        let ind = self.orientation as usize;
+        let viewport: [f32; 2] = From::from(self.viewport().size());
+        0f32.max(self.content_length - viewport[ind])
+    }
+
+    /// Set the scroll offset, clamped between 0 and `max_offset`
+    pub fn set_offset(&mut self, offset: f32) {
+        let offset = offset.max(0f32).min(self.max_offset());
+        if offset != self.offset {
+            self.offset = offset;
+            self.common.invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+        }
+    }
+
+    /// Add `delta` to the scroll offset, clamped between 0 and `max_offset`
+    pub fn scroll_by(&mut self, delta: f32) {
+        let offset = self.offset + delta;
+        self.set_offset(offset);
+    }
+
+    /// The visible area, in the local coordinates of the view
+    fn viewport(&self) -> FRect {
+        let size = self.common.rect.size();
+        FRect::new_s(0f32, 0f32, size) - self.padding()
+    }
+}
+
+impl View for ScrollView {}
+
+impl view::Measure for ScrollView {
+    fn measure(&mut self, specs: [MeasureSpec; 2]) {
+        let ind = self.orientation as usize;
+        let ind_ortho = self.orientation.ortho() as usize;
+        let padding: [f32; 2] = [self.padding().horizontal(), self.padding().vertical()];
+
+        let mut content = [0f32; 2];
+        if let Some(node) = self.children().skip_gone().next() {
+            let mut view = node.view_mut();
+            let params = view.layout_params();
+            let sizes = [params.width, params.height];
+            let mut child_specs = [MeasureSpec::Unspecified; 2];
+            child_specs[ind_ortho] =
+                child_measure_spec(specs[ind_ortho], padding[ind_ortho], sizes[ind_ortho]);
+            measure_if_dirty(&mut *view, child_specs);
+            content = From::from(view.measurement());
+        }
+        self.content_length = content[ind];
+
+        self.set_constrained_measurement(
+            Size(
+                resolve_size(content[0] + padding[0], specs[0]),
+                resolve_size(content[1] + padding[1], specs[1]),
+            ),
+            specs,
+        );
+    }
+}
+
+impl view::Layout for ScrollView {
+    fn layout(&mut self, rect: FRect) {
+        self.common.rect = rect;
+        // the viewport may have shrunk
+        self.offset = self.offset.max(0f32).min(self.max_offset());
+
+        let ind = self.orientation as usize;
+        let viewport = self.viewport();
+        if let Some(node) = self.children().skip_gone().next() {
+            let mut view = node.view_mut();
+            let mes = view.measurement();
+            let mut point: [f32; 2] = From::from(viewport.point());
+            point[ind] -= self.offset;
+            layout_if_dirty(&mut *view, FRect::new_s(point[0], point[1], mes));
+        }
+    }
+}
+
+impl view::GestureHandler for ScrollView {}
+
+impl view::PointerHandler for ScrollView {
+    fn on_wheel(&mut self, _pos: FPoint, delta: FVec) -> bool {
+        let before = self.offset;
+        // wheel delta is positive when scrolling towards the start of the content
+        self.scroll_by(-delta[self.orientation as usize]);
+        self.offset != before
+    }
+}
+
+impl view::KeyHandler for ScrollView {}
+
+impl view::FrameRender for ScrollView {
+    fn frame_render(&self) -> Option<frame::Node> {
+        None
+    }
+
+    fn frame_clip(&self) -> Option<FRect> {
+        Some(self.viewport())
+    }
+}
+
+impl view::Base for ScrollView {
+    type Style = ();
+    type State = ();
+
+    fn common(&self) -> &view::Common {
+        &self.common
+    }
+    fn common_mut(&mut self) -> &mut view::Common {
+        &mut self.common
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::tests::{move_to, set_up, wheel, Event, Probe};
+    use crate::geom::Point;
+    use crate::ui::{Node, UserInterface, WHEEL_LINE_LENGTH};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// A 100x100 vertical scroll view around a 100x300 probe
+    fn scroll_ui(probe: Probe) -> (Rc<UserInterface>, Rc<Node>, Rc<Node>) {
+        let ui = UserInterface::new();
+        let node = Node::new(ScrollView::new_vertical(), ui.clone(), None);
+        let child = Node::new(probe, ui.clone(), None);
+        node.add_child(&child, None);
+        set_up(&ui, &node, (100f64, 100f64));
+        (ui, node, child)
+    }
+
+    fn probe(scrollable: bool) -> (Probe, Rc<RefCell<Vec<Event>>>) {
+        let size = Size(100f32, 300f32);
+        let probe = if scrollable {
+            Probe::new_scrollable(size)
+        } else {
+            Probe::new(size)
+        };
+        let events = probe.events();
+        (probe, events)
+    }
+
+    #[test]
+    fn scroll_by_clamps() {
+        let (ui, node, child) = scroll_ui(probe(false).0);
+        assert_eq!(node.view_as::<ScrollView>().max_offset(), 200f32);
+
+        node.view_as_mut::<ScrollView>().scroll_by(-10f32);
+        assert_eq!(node.view_as::<ScrollView>().offset(), 0f32);
+
+        node.view_as_mut::<ScrollView>().scroll_by(50f32);
+        ui.layout();
+        assert_eq!(node.view_as::<ScrollView>().offset(), 50f32);
+        assert_eq!(child.view().rect(), FRect::new(0f32, -50f32, 100f32, 300f32));
+
+        node.view_as_mut::<ScrollView>().scroll_by(500f32);
+        ui.layout();
+        assert_eq!(node.view_as::<ScrollView>().offset(), 200f32);
+        assert_eq!(child.view().rect(), FRect::new(0f32, -200f32, 100f32, 300f32));
+    }
+
+    #[test]
+    fn wheel_bubbles_through_child() {
+        let (probe, events) = probe(false);
+        let (ui, node, _) = scroll_ui(probe);
+        move_to(&ui, 50f64, 50f64);
+        events.borrow_mut().clear();
+        wheel(&ui, -2f32);
+        assert_eq!(*events.borrow(), vec![Event::Wheel(Point(50f32, 50f32))]);
+        assert_eq!(node.view_as::<ScrollView>().offset(), 2f32 * WHEEL_LINE_LENGTH);
+    }
+
+    #[test]
+    fn wheel_consumed_by_child() {
+        let (probe, events) = probe(true);
+        let (ui, node, _) = scroll_ui(probe);
+        move_to(&ui, 50f64, 50f64);
+        events.borrow_mut().clear();
+        wheel(&ui, -2f32);
+        assert_eq!(*events.borrow(), vec![Event::Wheel(Point(50f32, 50f32))]);
+        assert_eq!(node.view_as::<ScrollView>().offset(), 0f32);
+    }
+}
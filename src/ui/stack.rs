@@ -0,0 +1,147 @@
+use crate::geom::FRect;
+use crate::render::frame;
+use crate::ui::layout::{layout_if_dirty, measure_largest_child};
+use crate::ui::view::{self, Base, Children, HasPadding, MeasureSpec, View};
+
+/// A container overlapping its children. It is sized to its largest child and
+/// places each child according to the gravity of the child layout params.
+/// Children are drawn in order, the last child on top.
+#[derive(Debug)]
+pub struct StackLayout {
+    common: view::Common,
+}
+
+impl view::HasChildren for StackLayout {}
+
+impl StackLayout {
+    pub fn new() -> StackLayout {
+        StackLayout {
+            common: view::Common::default(),
+        }
+    }
+}
+
+impl Default for StackLayout {
+    fn default() -> StackLayout {
+        StackLayout::new()
+    }
+}
+
+impl View for StackLayout {}
+
+impl view::Measure for StackLayout {
+    fn measure(&mut self, specs: [MeasureSpec; 2]) {
+        let size = measure_largest_child(self.children().skip_gone(), self.padding(), specs);
+        self.set_constrained_measurement(size, specs);
+    }
+}
+
+impl view::Layout for StackLayout {
+    fn layout(&mut self, rect: FRect) {
+        self.common.rect = rect;
+        let padding = self.padding();
+        for node in self.children().skip_gone() {
+            let mut view = node.view_mut();
+            let gravity = view.layout_params().gravity;
+            let margins = view.margins();
+            let mes = view.measurement();
+            let (x, width) = gravity.horizontal().place(
+                rect.width - padding.horizontal() - margins.horizontal(),
+                mes.width(),
+            );
+            let (y, height) = gravity.vertical().place(
+                rect.height - padding.vertical() - margins.vertical(),
+                mes.height(),
+            );
+            layout_if_dirty(&mut *view, FRect::new(
+                padding.left() + margins.left() + x,
+                padding.top() + margins.top() + y,
+                width,
+                height,
+            ));
+        }
+    }
+}
+
+impl view::GestureHandler for StackLayout {}
+
+impl view::PointerHandler for StackLayout {}
+
+impl view::KeyHandler for StackLayout {}
+
+impl view::FrameRender for StackLayout {
+    fn frame_render(&self) -> Option<frame::Node> {
+        None
+    }
+}
+
+impl view::Base for StackLayout {
+    type Style = ();
+    type State = ();
+
+    fn common(&self) -> &view::Common {
+        &self.common
+    }
+    fn common_mut(&mut self) -> &mut view::Common {
+        &mut self.common
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::{Margins, Size};
+    use crate::ui::layout::{Gravity, Params};
+    use crate::ui::tests::set_up;
+    use crate::ui::{Label, Node, UserInterface};
+    use std::rc::Rc;
+
+    /// A 10x10 label placed with `gravity`
+    fn small(gravity: Gravity) -> Label {
+        let mut label = Label::default();
+        label.set_min_size(Size(10f32, 10f32));
+        label.set_layout_params(Params {
+            gravity,
+            ..Params::default()
+        });
+        label
+    }
+
+    /// A stack sized by a 100x80 label, with `child` on top of it
+    fn stack_ui(child: Label) -> (Rc<UserInterface>, Rc<Node>, Rc<Node>) {
+        let ui = UserInterface::new();
+        let node = Node::new(StackLayout::new(), ui.clone(), None);
+        let mut big = Label::default();
+        big.set_min_size(Size(100f32, 80f32));
+        node.add_child(&Node::new(big, ui.clone(), None), None);
+        let child = Node::new(child, ui.clone(), None);
+        node.add_child(&child, None);
+        (ui, node, child)
+    }
+
+    #[test]
+    fn bottom_right() {
+        let (ui, node, child) = stack_ui(small(Gravity::BOTTOM_RIGHT));
+        set_up(&ui, &node, (200f64, 200f64));
+        assert_eq!(node.view().measurement(), Size(100f32, 80f32));
+        assert_eq!(child.view().rect(), FRect::new(90f32, 70f32, 10f32, 10f32));
+    }
+
+    #[test]
+    fn centered() {
+        let (ui, node, child) = stack_ui(small(Gravity::CENTER));
+        set_up(&ui, &node, (200f64, 200f64));
+        assert_eq!(child.view().rect(), FRect::new(45f32, 35f32, 10f32, 10f32));
+    }
+
+    #[test]
+    fn padding_and_margins() {
+        let mut label = small(Gravity::BOTTOM_RIGHT);
+        label.set_margins(Margins(0f32, 0f32, 3f32, 4f32));
+        let (ui, node, child) = stack_ui(label);
+        node.view_as_mut::<StackLayout>().set_padding(Margins(5f32, 5f32, 5f32, 5f32));
+        set_up(&ui, &node, (200f64, 200f64));
+        assert_eq!(node.view().measurement(), Size(110f32, 90f32));
+        assert_eq!(child.view().rect(), FRect::new(92f32, 71f32, 10f32, 10f32));
+    }
+}
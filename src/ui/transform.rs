@@ -0,0 +1,167 @@
+use crate::geom::{FPoint, FRect, Point, Vec};
+use crate::render::frame;
+use crate::ui::layout::{layout_if_dirty, measure_largest_child};
+use crate::ui::view::{self, Base, Children, HasPadding, MeasureSpec, View};
+use crate::Transform;
+
+/// A container rotating and scaling its children about a pivot.
+/// Children are laid out over the padded rect of the view, as if untransformed.
+/// The transform only affects rendering and hit-testing, not the layout of the siblings.
+#[derive(Debug)]
+pub struct TransformView {
+    common: view::Common,
+    pivot: FPoint,
+    rotation: f32,
+    scale: [f32; 2],
+}
+
+impl view::HasChildren for TransformView {}
+
+impl TransformView {
+    pub fn new() -> TransformView {
+        TransformView {
+            common: view::Common::default(),
+            pivot: Point(0.5f32, 0.5f32),
+            rotation: 0f32,
+            scale: [1f32, 1f32],
+        }
+    }
+
+    /// The point about which children are rotated and scaled, as a fraction of the view size
+    pub fn pivot(&self) -> FPoint {
+        self.pivot
+    }
+
+    /// Set the pivot, as a fraction of the view size. The default is the center.
+    pub fn set_pivot(&mut self, pivot: FPoint) {
+        self.pivot = pivot;
+        self.update_transform();
+    }
+
+    /// The rotation in radians, in the same direction as `Transform::rotate`
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    pub fn set_rotation(&mut self, radians: f32) {
+        self.rotation = radians;
+        self.update_transform();
+    }
+
+    pub fn scale(&self) -> [f32; 2] {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, factors: [f32; 2]) {
+        self.scale = factors;
+        self.update_transform();
+    }
+
+    /// Compose the view transform from the pivot, rotation and scale.
+    /// The transform depends on the view size and is updated at each layout.
+    fn update_transform(&mut self) {
+        let size = self.common.rect.size();
+        let pivot = Vec(self.pivot.0 * size.width(), self.pivot.1 * size.height());
+        let transform = Transform::translation(Vec(-pivot.0, -pivot.1))
+            .scale(self.scale)
+            .rotate(self.rotation)
+            .translate(pivot);
+        if transform != self.common.transform {
+            self.set_transform(transform);
+        }
+    }
+}
+
+impl Default for TransformView {
+    fn default() -> TransformView {
+        TransformView::new()
+    }
+}
+
+impl View for TransformView {}
+
+impl view::Measure for TransformView {
+    fn measure(&mut self, specs: [MeasureSpec; 2]) {
+        let size = measure_largest_child(self.children().skip_gone(), self.padding(), specs);
+        self.set_constrained_measurement(size, specs);
+    }
+}
+
+impl view::Layout for TransformView {
+    fn layout(&mut self, rect: FRect) {
+        self.common.rect = rect;
+        self.update_transform();
+        let content = FRect::new_s(0f32, 0f32, rect.size()) - self.padding();
+        for node in self.children().skip_gone() {
+            let mut view = node.view_mut();
+            let margins = view.margins();
+            layout_if_dirty(&mut *view, content - margins);
+        }
+    }
+}
+
+impl view::GestureHandler for TransformView {}
+
+impl view::PointerHandler for TransformView {}
+
+impl view::KeyHandler for TransformView {}
+
+impl view::FrameRender for TransformView {
+    fn frame_render(&self) -> Option<frame::Node> {
+        None
+    }
+}
+
+impl view::Base for TransformView {
+    type Style = ();
+    type State = ();
+
+    fn common(&self) -> &view::Common {
+        &self.common
+    }
+    fn common_mut(&mut self) -> &mut view::Common {
+        &mut self.common
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geom::Size;
+    use crate::ui::tests::{render, set_up};
+    use crate::ui::{Label, Node, UserInterface};
+    use std::f32::consts::PI;
+
+    #[test]
+    fn child_frame_transform() {
+        let ui = UserInterface::new();
+        let mut view = TransformView::new();
+        view.set_rotation(PI / 2f32);
+        view.set_scale([2f32, 1f32]);
+        let node = Node::new(view, ui.clone(), None);
+        let mut label = Label::default();
+        label.set_min_size(Size(100f32, 50f32));
+        node.add_child(&Node::new(label, ui.clone(), None), None);
+        set_up(&ui, &node, (200f64, 200f64));
+
+        let expected = node.view().transform();
+        assert!(expected != Transform::identity());
+        match render(&ui) {
+            Some(frame::Node::Transform(child, mat)) => {
+                assert_eq!(mat, expected.to_4x4_col_major());
+                match *child {
+                    frame::Node::Group(ref nodes) => assert_eq!(nodes.len(), 1),
+                    _ => panic!("expected the group of the children"),
+                }
+                // the pivot, at the center, is not moved
+                let t = Transform::from_4x4_col_major(&mat);
+                let pivot = t * Point(50f32, 25f32);
+                assert!((pivot.0 - 50f32).abs() < 1e-4 && (pivot.1 - 25f32).abs() < 1e-4);
+                // the scaled top-left corner is rotated by a quarter turn about the pivot
+                let corner = t * Point(0f32, 0f32);
+                assert!((corner.0 - 75f32).abs() < 1e-4 && (corner.1 + 75f32).abs() < 1e-4);
+            }
+            _ => panic!("expected a transform node"),
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use crate::geom::{FMargins, FPoint, FRect, FSize, Margins, Size};
+use crate::geom::{FMargins, FPoint, FRect, FSize, FVec, Margins, Size};
 use crate::render::frame;
-use crate::ui::Node;
+use crate::ui::{self, layout, Node};
 use crate::Transform;
 
 use downcast_rs::Downcast;
@@ -13,17 +13,36 @@ use std::rc::{Rc, Weak};
 /// The View trait represent a single or composed view in a view tree.
 /// The View trait is object safe.
 pub trait View:
-    Debug + Downcast + NodeOwned + Measure + Layout + FrameRender + HasRect + HasPadding + HasMargins
+    Debug
+    + Downcast
+    + NodeOwned
+    + Measure
+    + Layout
+    + FrameRender
+    + HasRect
+    + HasPadding
+    + HasMargins
+    + HasTransform
+    + HasOpacity
+    + HasVisibility
+    + HasEnabled
+    + HasFocusable
+    + HasLayoutParams
+    + GestureHandler
+    + PointerHandler
+    + KeyHandler
 {
 }
 
 impl_downcast!(View);
 
 /// Specify how a View should measure itself
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum MeasureSpec {
     Unspecified,
     AtMost(f32),
+    /// The view can grow as needed but must be at least this size
+    AtLeast(f32),
     Exactly(f32),
 }
 
@@ -53,8 +72,14 @@ pub trait Layout {
 
 /// Trait for objects that can render themselves in a framegraph node
 pub trait FrameRender {
-    /// Render the object in a frame graph node
+    /// Render the object in a frame graph node, in the coordinates of its parent.
+    /// Children, transform, opacity and visibility are handled by the user interface.
     fn frame_render(&self) -> Option<frame::Node>;
+    /// The rect, in local coordinates, outside of which the children are not visible.
+    /// By default children are not clipped.
+    fn frame_clip(&self) -> Option<FRect> {
+        None
+    }
 }
 
 /// Object that has an assigned Rect within its parent
@@ -85,6 +110,97 @@ pub trait HasPadding {
     fn padding(&self) -> FMargins; // left, top, right, bottom
 }
 
+/// View that has a transform applied to its content, relative to its rect origin
+pub trait HasTransform {
+    fn transform(&self) -> Transform;
+}
+
+/// View that has an opacity applied to itself and its children
+pub trait HasOpacity {
+    /// The opacity, from 0 (transparent) to 1 (opaque)
+    fn opacity(&self) -> f32;
+}
+
+/// Whether a view is drawn and takes space in its parent layout
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visibility {
+    /// Drawn and laid out
+    Visible,
+    /// Not drawn but laid out, so that it keeps its space
+    Hidden,
+    /// Neither drawn nor laid out, as if removed from its parent
+    Gone,
+}
+
+/// View that can be hidden
+pub trait HasVisibility {
+    fn visibility(&self) -> Visibility;
+}
+
+/// View that has parameters for its parent layout
+pub trait HasLayoutParams {
+    fn layout_params(&self) -> layout::Params;
+}
+
+/// View that can be enabled or disabled for user interaction
+pub trait HasEnabled {
+    /// Whether the view accepts pointer and keyboard events
+    fn is_enabled(&self) -> bool;
+    /// Enable or disable user interaction with the view
+    fn set_enabled(&mut self, enabled: bool);
+}
+
+/// View that can receive the keyboard focus
+pub trait HasFocusable {
+    /// Whether the view can receive the keyboard focus
+    fn is_focusable(&self) -> bool;
+    /// Allow or disallow the view to receive the keyboard focus
+    fn set_focusable(&mut self, focusable: bool);
+}
+
+/// Trait for views that respond to keyboard events when focused.
+/// Default implementations do nothing.
+pub trait KeyHandler {
+    /// Called when the view gains or loses the keyboard focus
+    fn on_focus(&mut self, _focused: bool) {}
+    /// Called when a key is pressed or released while the view has the focus
+    fn on_key(&mut self, _input: winit::KeyboardInput) {}
+    /// Called when a character is received while the view has the focus
+    fn on_char(&mut self, _c: char) {}
+}
+
+/// Trait for views that respond to gestures.
+/// Positions are given in the view local coordinates. Default implementations do nothing.
+pub trait GestureHandler {
+    /// Called when the view is pressed and released within a short time and distance
+    fn on_tap(&mut self, _pos: FPoint) {}
+    /// Called when a tap follows a first one within a short time and distance
+    fn on_double_tap(&mut self, _pos: FPoint) {}
+    /// Called when the view is pressed and released after a long time
+    fn on_long_press(&mut self, _pos: FPoint) {}
+}
+
+/// Trait for views that respond to raw pointer events.
+/// Positions are given in the view local coordinates. Default implementations do nothing.
+pub trait PointerHandler {
+    /// Called when the pointer is pressed over the view
+    fn on_pointer_down(&mut self, _pos: FPoint) {}
+    /// Called when the pointer is released after having been pressed over the view,
+    /// even if it was moved out of the view in between
+    fn on_pointer_up(&mut self, _pos: FPoint) {}
+    /// Called when the pointer moves over the view, or anywhere while the view is pressed
+    fn on_pointer_move(&mut self, _pos: FPoint) {}
+    /// Called when the pointer starts hovering the view
+    fn on_pointer_enter(&mut self) {}
+    /// Called when the pointer stops hovering the view
+    fn on_pointer_leave(&mut self) {}
+    /// Called when the mouse wheel is rolled over the view. Returns whether the event
+    /// was consumed. Events not consumed are delivered to the parent view.
+    fn on_wheel(&mut self, _pos: FPoint, _delta: FVec) -> bool {
+        false
+    }
+}
+
 impl<T: HasRect> HasPosition for T {
     fn position(&self) -> FPoint {
         self.rect().point()
@@ -179,6 +295,83 @@ pub trait Base: View {
     fn set_measurement(&mut self, size: FSize) {
         self.common_mut().measurement = size;
     }
+
+    /// Store the measurement after having applied the aspect ratio constraint if any
+    fn set_constrained_measurement(&mut self, size: FSize, specs: [MeasureSpec; 2]) {
+        let size = match self.common().aspect_ratio {
+            Some(ratio) => constrain_aspect_ratio(size, ratio, specs),
+            None => size,
+        };
+        self.set_measurement(size);
+    }
+
+    /// The width / height ratio imposed to the view measurement
+    fn aspect_ratio(&self) -> Option<f32> {
+        self.common().aspect_ratio
+    }
+
+    fn set_padding(&mut self, padding: FMargins) {
+        self.common_mut().padding = padding;
+        self.common().invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+
+    fn set_margins(&mut self, margins: FMargins) {
+        self.common_mut().margins = margins;
+        self.common().invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+
+    fn set_transform(&mut self, transform: Transform) {
+        self.common_mut().transform = transform;
+        self.common().invalidate(ui::Dirty::FRAME);
+    }
+
+    /// Set the opacity, clamped between 0 (transparent) and 1 (opaque)
+    fn set_opacity(&mut self, opacity: f32) {
+        self.common_mut().opacity = opacity.max(0f32).min(1f32);
+        self.common().invalidate(ui::Dirty::FRAME);
+    }
+
+    fn set_visibility(&mut self, visibility: Visibility) {
+        self.common_mut().visibility = visibility;
+        self.common().invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+
+    fn set_layout_params(&mut self, params: layout::Params) {
+        self.common_mut().layout_params = params;
+        self.common().invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+
+    fn set_aspect_ratio(&mut self, ratio: Option<f32>) {
+        debug_assert!(ratio.map_or(true, |r| r > 0f32));
+        self.common_mut().aspect_ratio = ratio;
+        self.common().invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+}
+
+/// Constrain a measured size to the width / height `ratio`.
+/// An `Exactly` spec on one axis drives the other axis. Otherwise the largest size
+/// having the ratio is fitted in the `AtMost` bounds, or in the measured `size` for
+/// `Unspecified` axes. `AtLeast` axes use the measured `size` grown to their bound.
+pub fn constrain_aspect_ratio(size: FSize, ratio: f32, specs: [MeasureSpec; 2]) -> FSize {
+    let clamp = |spec: MeasureSpec, val: f32| match spec {
+        MeasureSpec::AtMost(max) => val.min(max),
+        MeasureSpec::AtLeast(min) => val.max(min),
+        _ => val,
+    };
+    match specs {
+        [MeasureSpec::Exactly(w), MeasureSpec::Exactly(h)] => Size(w, h),
+        [MeasureSpec::Exactly(w), hs] => Size(w, clamp(hs, w / ratio)),
+        [ws, MeasureSpec::Exactly(h)] => Size(clamp(ws, h * ratio), h),
+        [ws, hs] => {
+            let bound = |spec: MeasureSpec, val: f32| match spec {
+                MeasureSpec::AtMost(max) => max,
+                MeasureSpec::AtLeast(min) => val.max(min),
+                _ => val,
+            };
+            let w = bound(ws, size.width()).min(bound(hs, size.height()) * ratio);
+            Size(w, w / ratio)
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -189,6 +382,22 @@ pub struct Common {
     pub padding: FMargins,
     pub margins: FMargins,
     pub transform: Transform,
+    pub opacity: f32,
+    pub visibility: Visibility,
+    pub enabled: bool,
+    pub focusable: bool,
+    pub aspect_ratio: Option<f32>,
+    pub layout_params: layout::Params,
+}
+
+impl Common {
+    /// Mark the node owning this view, its ancestors and the user interface
+    /// with the given dirty flags
+    pub fn invalidate(&self, flags: ui::Dirty) {
+        if let Some(node) = self.node.upgrade() {
+            node.mark_dirty(flags);
+        }
+    }
 }
 
 impl<T: Base> NodeOwned for T {
@@ -221,20 +430,71 @@ impl<T: Base> HasMargins for T {
     }
 }
 
+impl<T: Base> HasTransform for T {
+    fn transform(&self) -> Transform {
+        self.common().transform
+    }
+}
+
+impl<T: Base> HasOpacity for T {
+    fn opacity(&self) -> f32 {
+        self.common().opacity
+    }
+}
+
+impl<T: Base> HasVisibility for T {
+    fn visibility(&self) -> Visibility {
+        self.common().visibility
+    }
+}
+
+impl<T: Base> HasLayoutParams for T {
+    fn layout_params(&self) -> layout::Params {
+        self.common().layout_params
+    }
+}
+
+impl<T: Base> HasEnabled for T {
+    fn is_enabled(&self) -> bool {
+        self.common().enabled
+    }
+    fn set_enabled(&mut self, enabled: bool) {
+        if self.common().enabled != enabled {
+            self.common_mut().enabled = enabled;
+            self.common().invalidate(ui::Dirty::FRAME);
+        }
+    }
+}
+
+impl<T: Base> HasFocusable for T {
+    fn is_focusable(&self) -> bool {
+        self.common().focusable
+    }
+    fn set_focusable(&mut self, focusable: bool) {
+        if self.common().focusable != focusable {
+            self.common_mut().focusable = focusable;
+            self.common().invalidate(ui::Dirty::FRAME);
+        }
+    }
+}
+
 pub struct ChildrenIter {
     sibling: Option<Rc<Node>>,
 }
 
+impl ChildrenIter {
+    /// Skip the `Gone` children, which take no part in layout
+    pub fn skip_gone(self) -> impl Iterator<Item = Rc<Node>> {
+        self.filter(|node| node.view().visibility() != Visibility::Gone)
+    }
+}
+
 impl Iterator for ChildrenIter {
     type Item = Rc<Node>;
     fn next(&mut self) -> Option<Self::Item> {
-        match &self.sibling {
-            None => None,
-            Some(node) => {
-                self.sibling = node.next_sibling();
-                self.sibling.clone()
-            }
-        }
+        let node = self.sibling.take()?;
+        self.sibling = node.next_sibling();
+        Some(node)
     }
 }
 
@@ -257,6 +517,12 @@ impl Default for Common {
             padding: Margins(0f32, 0f32, 0f32, 0f32),
             margins: Margins(0f32, 0f32, 0f32, 0f32),
             transform: Transform::identity(),
+            opacity: 1f32,
+            visibility: Visibility::Visible,
+            enabled: true,
+            focusable: false,
+            aspect_ratio: None,
+            layout_params: layout::Params::default(),
         }
     }
 }
@@ -269,3 +535,71 @@ bitflags! {
         const TRANSFORM = 8;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::{Label, LinearLayout, UserInterface};
+
+    fn children_of(parent: &Node) -> Vec<Rc<Node>> {
+        parent.view_as::<LinearLayout>().children().collect()
+    }
+
+    #[test]
+    fn children_empty() {
+        let ui = UserInterface::new();
+        let parent = Node::new(LinearLayout::new_vertical(), ui, None);
+        assert!(!parent.has_children());
+        assert!(children_of(&parent).is_empty());
+    }
+
+    #[test]
+    fn children_single() {
+        let ui = UserInterface::new();
+        let parent = Node::new(LinearLayout::new_vertical(), ui.clone(), None);
+        let child = Node::new(Label::default(), ui, None);
+        parent.add_child(&child, None);
+        assert!(parent.has_children());
+        let children = children_of(&parent);
+        assert_eq!(children.len(), 1);
+        assert!(children[0].is_same(&child));
+    }
+
+    #[test]
+    fn children_multiple() {
+        let ui = UserInterface::new();
+        let parent = Node::new(LinearLayout::new_vertical(), ui.clone(), None);
+        let c: Vec<_> = (0..3)
+            .map(|_| Node::new(Label::default(), ui.clone(), None))
+            .collect();
+        parent.add_child(&c[0], None);
+        parent.add_child(&c[2], None);
+        parent.add_child(&c[1], Some(&c[2]));
+        let children = children_of(&parent);
+        assert_eq!(children.len(), 3);
+        for (child, expected) in children.iter().zip(c.iter()) {
+            assert!(child.is_same(expected));
+        }
+    }
+
+    #[test]
+    fn children_skip_gone() {
+        let ui = UserInterface::new();
+        let parent = Node::new(LinearLayout::new_vertical(), ui.clone(), None);
+        let c: Vec<_> = (0..3)
+            .map(|_| Node::new(Label::default(), ui.clone(), None))
+            .collect();
+        for n in &c {
+            parent.add_child(n, None);
+        }
+        c[1].view_as_mut::<Label>().set_visibility(Visibility::Gone);
+        let visible: Vec<_> = parent
+            .view_as::<LinearLayout>()
+            .children()
+            .skip_gone()
+            .collect();
+        assert_eq!(visible.len(), 2);
+        assert!(visible[0].is_same(&c[0]));
+        assert!(visible[1].is_same(&c[2]));
+    }
+}
@@ -0,0 +1,229 @@
+use crate::geom::{FPoint, FRect, FSize, Point, Size};
+use crate::render::frame;
+use crate::ui;
+use crate::ui::layout::{child_measure_spec, layout_if_dirty, measure_if_dirty, resolve_size};
+use crate::ui::view::{self, Base, Children, HasPadding, MeasureSpec, View};
+
+/// A container laying its children out left to right, and wrapping to a new line
+/// when the next child would exceed the available width.
+#[derive(Debug)]
+pub struct WrapLayout {
+    common: view::Common,
+    spacing: f32,
+    line_spacing: f32,
+}
+
+impl view::HasChildren for WrapLayout {}
+
+impl WrapLayout {
+    pub fn new() -> WrapLayout {
+        WrapLayout {
+            common: view::Common::default(),
+            spacing: 0f32,
+            line_spacing: 0f32,
+        }
+    }
+
+    /// The space between two children of the same line
+    pub fn spacing(&self) -> f32 {
+        self.spacing
+    }
+
+    pub fn set_spacing(&mut self, spacing: f32) {
+        self.spacing = spacing;
+        self.common.invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+
+    /// The space between two lines
+    pub fn line_spacing(&self) -> f32 {
+        self.line_spacing
+    }
+
+    pub fn set_line_spacing(&mut self, line_spacing: f32) {
+        self.line_spacing = line_spacing;
+        self.common.invalidate(ui::Dirty::LAYOUT | ui::Dirty::FRAME);
+    }
+
+    /// Flow the measured children in lines of at most `width` (unbounded if `None`).
+    /// Returns the position of each child rect, relative to the content origin,
+    /// and the size of the content.
+    fn flow(&self, width: Option<f32>) -> (Vec<FPoint>, FSize) {
+        let mut positions = Vec::new();
+        let mut content = Size(0f32, 0f32);
+        let mut line_y = 0f32;
+        let mut line_width = 0f32;
+        let mut line_height = 0f32;
+        let mut line_count = 0;
+
+        for node in self.children().skip_gone() {
+            let view = node.view();
+            let mes = view.measurement();
+            let margins = view.margins();
+            let w = mes.width() + margins.horizontal();
+            let h = mes.height() + margins.vertical();
+
+            let wraps = line_count > 0
+                && width.map_or(false, |width| line_width + self.spacing + w > width);
+            if wraps {
+                line_y += line_height + self.line_spacing;
+                line_height = 0f32;
+                line_count = 0;
+            }
+            let x = if line_count == 0 {
+                0f32
+            } else {
+                line_width + self.spacing
+            };
+
+            positions.push(Point(x + margins.left(), line_y + margins.top()));
+            line_width = x + w;
+            line_height = line_height.max(h);
+            line_count += 1;
+            content = Size(content.width().max(line_width), line_y + line_height);
+        }
+
+        (positions, content)
+    }
+}
+
+impl Default for WrapLayout {
+    fn default() -> WrapLayout {
+        WrapLayout::new()
+    }
+}
+
+impl View for WrapLayout {}
+
+impl view::Measure for WrapLayout {
+    fn measure(&mut self, specs: [MeasureSpec; 2]) {
+        let padding = self.padding();
+        for node in self.children().skip_gone() {
+            let mut view = node.view_mut();
+            let params = view.layout_params();
+            let ws = child_measure_spec(specs[0], padding.horizontal(), params.width);
+            let hs = child_measure_spec(specs[1], padding.vertical(), params.height);
+            measure_if_dirty(&mut *view, [ws, hs]);
+        }
+
+        let width = match specs[0] {
+            MeasureSpec::AtMost(width) | MeasureSpec::Exactly(width) => {
+                Some(width - padding.horizontal())
+            }
+            MeasureSpec::AtLeast(_) | MeasureSpec::Unspecified => None,
+        };
+        let (_, content) = self.flow(width);
+
+        self.set_constrained_measurement(
+            Size(
+                resolve_size(content.width() + padding.horizontal(), specs[0]),
+                resolve_size(content.height() + padding.vertical(), specs[1]),
+            ),
+            specs,
+        );
+    }
+}
+
+impl view::Layout for WrapLayout {
+    fn layout(&mut self, rect: FRect) {
+        self.common.rect = rect;
+        let padding = self.padding();
+        let (positions, _) = self.flow(Some(rect.width - padding.horizontal()));
+        for (node, pos) in self.children().skip_gone().zip(positions) {
+            let mut view = node.view_mut();
+            let mes = view.measurement();
+            layout_if_dirty(&mut *view, FRect::new_s(
+                padding.left() + pos.0,
+                padding.top() + pos.1,
+                mes,
+            ));
+        }
+    }
+}
+
+impl view::GestureHandler for WrapLayout {}
+
+impl view::PointerHandler for WrapLayout {}
+
+impl view::KeyHandler for WrapLayout {}
+
+impl view::FrameRender for WrapLayout {
+    fn frame_render(&self) -> Option<frame::Node> {
+        None
+    }
+}
+
+impl view::Base for WrapLayout {
+    type Style = ();
+    type State = ();
+
+    fn common(&self) -> &view::Common {
+        &self.common
+    }
+    fn common_mut(&mut self) -> &mut view::Common {
+        &mut self.common
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::tests::set_up;
+    use crate::ui::{Label, Node, UserInterface};
+    use std::rc::Rc;
+
+    /// A wrap layout with 30 px wide children of heights 10, 11 and 12
+    fn wrap_ui() -> (Rc<UserInterface>, Rc<Node>) {
+        let ui = UserInterface::new();
+        let mut wrap = WrapLayout::new();
+        wrap.set_spacing(5f32);
+        wrap.set_line_spacing(2f32);
+        let node = Node::new(wrap, ui.clone(), None);
+        for i in 0..3 {
+            let mut label = Label::default();
+            label.set_min_size(Size(30f32, 10f32 + i as f32));
+            node.add_child(&Node::new(label, ui.clone(), None), None);
+        }
+        (ui, node)
+    }
+
+    fn child_rects(node: &Node) -> Vec<FRect> {
+        let wrap = node.view_as::<WrapLayout>();
+        wrap.children().map(|c| c.view().rect()).collect()
+    }
+
+    #[test]
+    fn one_line() {
+        let (ui, node) = wrap_ui();
+        set_up(&ui, &node, (200f64, 100f64));
+        assert_eq!(node.view().measurement(), Size(100f32, 12f32));
+        let rects = child_rects(&node);
+        assert_eq!(rects[0], FRect::new(0f32, 0f32, 30f32, 10f32));
+        assert_eq!(rects[1], FRect::new(35f32, 0f32, 30f32, 11f32));
+        assert_eq!(rects[2], FRect::new(70f32, 0f32, 30f32, 12f32));
+    }
+
+    #[test]
+    fn two_lines() {
+        let (ui, node) = wrap_ui();
+        set_up(&ui, &node, (80f64, 100f64));
+        assert_eq!(node.view().measurement(), Size(65f32, 25f32));
+        let rects = child_rects(&node);
+        assert_eq!(rects[1], FRect::new(35f32, 0f32, 30f32, 11f32));
+        assert_eq!(rects[2], FRect::new(0f32, 13f32, 30f32, 12f32));
+    }
+
+    #[test]
+    fn spacing_change_relayouts() {
+        let (ui, node) = wrap_ui();
+        set_up(&ui, &node, (80f64, 100f64));
+        node.view_as_mut::<WrapLayout>().set_line_spacing(10f32);
+        assert!(ui.dirty(ui::Dirty::LAYOUT));
+        ui.layout();
+        assert_eq!(child_rects(&node)[2], FRect::new(0f32, 21f32, 30f32, 12f32));
+        node.view_as_mut::<WrapLayout>().set_spacing(25f32);
+        ui.layout();
+        let rects = child_rects(&node);
+        assert_eq!(rects[1], FRect::new(0f32, 20f32, 30f32, 11f32));
+        assert_eq!(rects[2], FRect::new(0f32, 41f32, 30f32, 12f32));
+    }
+}